@@ -37,12 +37,10 @@
 use std::prelude::rust_2018::*;
 #[macro_use]
 extern crate std;
-#[cfg(
-    all(
-        not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "wasi"))),
-        feature = "proc-macro"
-    )
-)]
+#[cfg(all(
+    not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "wasi"))),
+    feature = "proc-macro"
+))]
 extern crate proc_macro;
 extern crate proc_macro2;
 #[cfg(feature = "printing")]
@@ -21529,17 +21527,10 @@ pub use crate::path::{
 };
 #[cfg(feature = "parsing")]
 pub mod buffer {
-    #[cfg(
-        all(
-            not(
-                all(
-                    target_arch = "wasm32",
-                    any(target_os = "unknown", target_os = "wasi")
-                )
-            ),
-            feature = "proc-macro"
-        )
-    )]
+    #[cfg(all(
+        not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "wasi"))),
+        feature = "proc-macro"
+    ))]
     use crate::proc_macro as pm;
     use crate::Lifetime;
     use proc_macro2::{
@@ -21599,17 +21590,13 @@ pub mod buffer {
             }
             TokenBuffer { ptr: entries, len }
         }
-        #[cfg(
-            all(
-                not(
-                    all(
-                        target_arch = "wasm32",
-                        any(target_os = "unknown", target_os = "wasi")
-                    )
-                ),
-                feature = "proc-macro"
-            )
-        )]
+        #[cfg(all(
+            not(all(
+                target_arch = "wasm32",
+                any(target_os = "unknown", target_os = "wasi")
+            )),
+            feature = "proc-macro"
+        ))]
         pub fn new(stream: pm::TokenStream) -> Self {
             Self::new2(stream.into())
         }
@@ -22743,13 +22730,11 @@ pub mod parse_quote {
         }
     }
 }
-#[cfg(
-    all(
-        not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "wasi"))),
-        feature = "parsing",
-        feature = "proc-macro"
-    )
-)]
+#[cfg(all(
+    not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "wasi"))),
+    feature = "parsing",
+    feature = "proc-macro"
+))]
 #[doc(hidden)]
 pub mod parse_macro_input {
     use crate::parse::{Parse, ParseStream, Parser, Result};
@@ -43585,17 +43570,10 @@ pub mod __private {
     pub extern crate quote;
     pub use proc_macro2::{Span, TokenStream as TokenStream2};
     pub use crate::span::IntoSpans;
-    #[cfg(
-        all(
-            not(
-                all(
-                    target_arch = "wasm32",
-                    any(target_os = "unknown", target_os = "wasi")
-                )
-            ),
-            feature = "proc-macro"
-        )
-    )]
+    #[cfg(all(
+        not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "wasi"))),
+        feature = "proc-macro"
+    ))]
     pub use proc_macro::TokenStream;
     #[cfg(feature = "printing")]
     pub use quote::{ToTokens, TokenStreamExt};
@@ -43858,17 +43836,10 @@ pub mod parse {
     use crate::buffer::{Cursor, TokenBuffer};
     use crate::error;
     use crate::lookahead;
-    #[cfg(
-        all(
-            not(
-                all(
-                    target_arch = "wasm32",
-                    any(target_os = "unknown", target_os = "wasi")
-                )
-            ),
-            feature = "proc-macro"
-        )
-    )]
+    #[cfg(all(
+        not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "wasi"))),
+        feature = "proc-macro"
+    ))]
     use crate::proc_macro;
     use crate::punctuated::Punctuated;
     use crate::token::Token;
@@ -44162,17 +44133,13 @@ pub mod parse {
     pub trait Parser: Sized {
         type Output;
         fn parse2(self, tokens: TokenStream) -> Result<Self::Output>;
-        #[cfg(
-            all(
-                not(
-                    all(
-                        target_arch = "wasm32",
-                        any(target_os = "unknown", target_os = "wasi")
-                    )
-                ),
-                feature = "proc-macro"
-            )
-        )]
+        #[cfg(all(
+            not(all(
+                target_arch = "wasm32",
+                any(target_os = "unknown", target_os = "wasi")
+            )),
+            feature = "proc-macro"
+        ))]
         fn parse(self, tokens: proc_macro::TokenStream) -> Result<Self::Output> {
             self.parse2(proc_macro2::TokenStream::from(tokens))
         }
@@ -44599,13 +44566,11 @@ mod error {
     }
 }
 pub use crate::error::{Error, Result};
-#[cfg(
-    all(
-        not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "wasi"))),
-        feature = "parsing",
-        feature = "proc-macro"
-    )
-)]
+#[cfg(all(
+    not(all(target_arch = "wasm32", any(target_os = "unknown", target_os = "wasi"))),
+    feature = "parsing",
+    feature = "proc-macro"
+))]
 pub fn parse<T: parse::Parse>(tokens: proc_macro::TokenStream) -> Result<T> {
     parse::Parser::parse(T::parse, tokens)
 }
@@ -0,0 +1,139 @@
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Format Rust source files using prettyplease.
+///
+/// With no paths, reads from stdin and writes to stdout. Otherwise each file
+/// is formatted in place, and each directory is searched recursively for .rs
+/// files to format.
+///
+/// Options are read from the rustfmt.toml or .rustfmt.toml nearest to each
+/// file, or to the current directory for stdin, the way rustfmt finds them.
+#[derive(Parser)]
+#[command(name = "prettyplease", version)]
+struct Args {
+    /// Files or directories to format
+    paths: Vec<PathBuf>,
+
+    /// Print the formatted output to stdout instead of overwriting files
+    #[arg(long)]
+    stdout: bool,
+
+    /// Do not write anything; exit with status 1 if any input is not already
+    /// formatted, listing the files that would change
+    #[arg(long, conflicts_with = "stdout")]
+    check: bool,
+
+    /// Target line width, overriding max_width from rustfmt.toml
+    #[arg(long, value_name = "N")]
+    width: Option<usize>,
+}
+
+fn main() {
+    let args = Args::parse();
+    match try_main(&args) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            let _ = writeln!(io::stderr(), "error: {err:#}");
+            process::exit(1);
+        }
+    }
+}
+
+// Returns false if some input failed to format, or if --check found an input
+// that is not already formatted.
+fn try_main(args: &Args) -> Result<bool> {
+    if args.paths.is_empty() {
+        let options = options(Path::new("."), args)?;
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        let formatted = format("<stdin>", &input, &options)?;
+        if args.check {
+            return Ok(formatted == input);
+        }
+        io::stdout().write_all(formatted.as_bytes())?;
+        return Ok(true);
+    }
+
+    let mut files = Vec::new();
+    for path in &args.paths {
+        if path.is_dir() {
+            collect_rs_files(path, &mut files)?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    let mut success = true;
+    for path in &files {
+        if let Err(err) = format_file(path, args, &mut success) {
+            let _ = writeln!(io::stderr(), "error: {err:#}");
+            success = false;
+        }
+    }
+    Ok(success)
+}
+
+// Options from the rustfmt configuration file that applies to `path`, with the
+// command-line flags applied on top.
+fn options(path: &Path, args: &Args) -> Result<prettyplease::Options> {
+    let mut config = prettyplease::RustfmtConfig::find(path)?;
+    if let Some(width) = args.width {
+        config.set_max_width(width);
+    }
+    Ok(config.options)
+}
+
+fn format_file(path: &Path, args: &Args, success: &mut bool) -> Result<()> {
+    let options = options(path, args)?;
+    let input =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let formatted = format(&path.display().to_string(), &input, &options)?;
+    if args.check {
+        if formatted != input {
+            println!("{}", path.display());
+            *success = false;
+        }
+    } else if args.stdout {
+        io::stdout().write_all(formatted.as_bytes())?;
+    } else if formatted != input {
+        fs::write(path, formatted)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn format(name: &str, input: &str, options: &prettyplease::Options) -> Result<String> {
+    let syntax_tree = match syn::parse_file(input) {
+        Ok(syntax_tree) => syntax_tree,
+        Err(err) => {
+            let span = err.span().start();
+            bail!("{}:{}:{}\n{}", name, span.line, span.column + 1, err);
+        }
+    };
+    prettyplease::try_unparse_with_comments(&syntax_tree, input, options)
+        .map_err(|err| anyhow!("{name}: unable to format {err}"))
+}
+
+fn collect_rs_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory {}", dir.display()))?
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(fs::DirEntry::path);
+    for entry in entries {
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_rs_files(&path, files)?;
+        } else if file_type.is_file() && path.extension() == Some(OsStr::new("rs")) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
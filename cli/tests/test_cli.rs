@@ -0,0 +1,116 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const FORMATTED: &str = "\
+// Entry point.
+fn main() {
+    // Set up.
+    let x = 1;
+
+    // Say hello.
+    println!(\"{}\", x);
+    // Done.
+}
+";
+
+const UNFORMATTED: &str = "\
+// Entry point.
+fn main() { /* Set up. */ let x = 1;
+
+    println!(\"{}\", x); // Say hello.
+    // Done.
+}
+";
+
+// Directory of files for one test, removed along with its contents when the
+// test is done.
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    fn new(test: &str) -> Self {
+        let name = format!("prettyplease-cli-{}-{}", std::process::id(), test);
+        let path = env::temp_dir().join(name);
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    fn write(&self, name: &str, contents: &str) -> PathBuf {
+        let path = self.path.join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn prettyplease(args: &[&str], path: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_prettyplease"))
+        .args(args)
+        .arg(path)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn stdout_keeps_comments() {
+    let dir = TempDir::new("stdout");
+    let path = dir.write("stdout.rs", UNFORMATTED);
+    let output = prettyplease(&["--stdout"], &path);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), FORMATTED);
+    assert_eq!(fs::read_to_string(&path).unwrap(), UNFORMATTED);
+}
+
+#[test]
+fn check_formatted_with_comments() {
+    let dir = TempDir::new("check-formatted");
+    let path = dir.write("formatted.rs", FORMATTED);
+    let output = prettyplease(&["--check"], &path);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn check_unformatted_with_comments() {
+    let dir = TempDir::new("check-unformatted");
+    let path = dir.write("unformatted.rs", UNFORMATTED);
+    let output = prettyplease(&["--check"], &path);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.trim_end(), path.display().to_string());
+    assert_eq!(fs::read_to_string(&path).unwrap(), UNFORMATTED);
+}
+
+#[test]
+fn in_place_keeps_comments() {
+    let dir = TempDir::new("in-place");
+    let path = dir.write("in_place.rs", UNFORMATTED);
+    let output = prettyplease(&[], &path);
+    assert!(output.status.success());
+    assert_eq!(fs::read_to_string(&path).unwrap(), FORMATTED);
+}
+
+#[test]
+fn rustfmt_toml() {
+    let dir = TempDir::new("rustfmt-toml");
+    dir.write("rustfmt.toml", "max_width = 70\nblank_lines_upper_bound = 2\n");
+    let input = "fn f() {\n    g(\"first argument\", \"second argument\", \"third argument\", \"fourth\");\n\n\n    h();\n}\n";
+    let path = dir.write("config.rs", input);
+
+    let output = prettyplease(&["--stdout"], &path);
+    assert!(output.status.success());
+    let expected = "fn f() {\n    g(\n        \"first argument\",\n        \"second argument\",\n        \"third argument\",\n        \"fourth\",\n    );\n\n\n    h();\n}\n";
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+
+    let output = prettyplease(&["--stdout", "--width", "120"], &path);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), input);
+}
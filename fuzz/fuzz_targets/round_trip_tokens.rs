@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use prettyplease::{Options, VerifyError};
+use std::str;
+
+fuzz_target!(|data: &[u8]| {
+    let ..=299 = data.len() else { return };
+    let Ok(string) = str::from_utf8(data) else { return };
+    let Ok(syntax_tree) = syn::parse_file(string) else { return };
+    match prettyplease::unparse_verified(&syntax_tree, &Options::default()) {
+        Ok(_) | Err(VerifyError::Unsupported(_)) => {}
+        Err(error) => panic!("{}", error),
+    }
+});
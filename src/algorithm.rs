@@ -1,12 +1,23 @@
 // Adapted from https://github.com/rust-lang/rust/blob/1.57.0/compiler/rustc_ast_pretty/src/pp.rs.
 // See "Algorithm notes" in the crate-level rustdoc.
 
+#[cfg(feature = "span-locations")]
+use crate::blank::BlankLines;
 use crate::ring::RingBuffer;
-use crate::{MARGIN, MIN_SPACE};
+use crate::{Options, SourceMap, UnparseError, WriteError, INDENT};
+#[cfg(feature = "comments")]
+use proc_macro2::LineColumn;
+use proc_macro2::Span;
 use std::borrow::Cow;
 use std::cmp;
+#[cfg(feature = "comments")]
+use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::fmt;
 use std::iter;
+use std::mem;
+#[cfg(feature = "comments")]
+use syn::Attribute;
 
 #[derive(Clone, Copy, PartialEq)]
 pub enum Breaks {
@@ -19,7 +30,7 @@ pub struct BreakToken {
     pub offset: isize,
     pub blank_space: usize,
     pub pre_break: Option<char>,
-    pub post_break: Option<char>,
+    pub post_break: Option<&'static str>,
     pub no_break: Option<char>,
     pub if_nonempty: bool,
     pub never_break: bool,
@@ -29,11 +40,13 @@ pub struct BreakToken {
 pub struct BeginToken {
     pub offset: isize,
     pub breaks: Breaks,
+    // Width beyond which the box breaks even if it would fit on the line
+    pub max_width: isize,
 }
 
 #[derive(Clone)]
 pub enum Token {
-    String(Cow<'static, str>),
+    String(Cow<'static, str>, Option<Span>),
     Break(BreakToken),
     Begin(BeginToken),
     End,
@@ -47,8 +60,20 @@ enum PrintFrame {
 
 pub const SIZE_INFINITY: isize = 0xffff;
 
-pub struct Printer {
+// Amount of output buffered before it is handed off to the sink, if any.
+const FLUSH_THRESHOLD: usize = 8 * 1024;
+
+pub struct Printer<'a> {
+    options: Options,
     out: String,
+    // Destination that completed lines are flushed to as printing progresses
+    sink: Option<&'a mut dyn fmt::Write>,
+    // Whether writing to the sink has failed
+    sink_error: bool,
+    // Number of bytes of output already flushed to the sink
+    flushed: usize,
+    // Output positions of tokens printed from spans, if requested
+    source_map: Option<SourceMap>,
     // Number of spaces left on line
     space: isize,
     // Ring-buffer of tokens and calculated sizes
@@ -69,6 +94,38 @@ pub struct Printer {
     indent: usize,
     // Buffered indentation to avoid writing trailing whitespace
     pending_indentation: usize,
+    // Whether the buffered indentation is at the start of a line
+    pending_line_start: bool,
+    // First syntax tree node encountered that could not be printed
+    error: Option<UnparseError>,
+    // Syntax tree nodes that were printed as plain tokens instead
+    fallbacks: Vec<UnparseError>,
+    // Original source text of the file being printed, if available
+    #[cfg(feature = "span-locations")]
+    source: Option<&'a str>,
+    // Blank lines from the original source to print ahead of syntax tree nodes
+    #[cfg(feature = "span-locations")]
+    blank_lines: BlankLines,
+    // Comments to print ahead of a closing brace, keyed by its position
+    #[cfg(feature = "comments")]
+    comments_before_close: HashMap<LineColumn, Vec<Attribute>>,
+}
+
+// Line endings within a single string token, such as a block comment or a
+// multi-line string literal, are made to match the ones between lines. A \r
+// already ahead of the \n is not doubled.
+fn replace_newlines(string: &str, newline: &str) -> String {
+    let mut replaced = String::with_capacity(string.len());
+    let mut lines = string.split('\n');
+    replaced.push_str(lines.next().unwrap());
+    for line in lines {
+        if replaced.ends_with('\r') {
+            replaced.pop();
+        }
+        replaced.push_str(newline);
+        replaced.push_str(line);
+    }
+    replaced
 }
 
 #[derive(Clone)]
@@ -77,11 +134,16 @@ struct BufEntry {
     size: isize,
 }
 
-impl Printer {
-    pub fn new() -> Self {
+impl<'a> Printer<'a> {
+    pub fn new(options: Options) -> Self {
         Printer {
+            space: options.max_width as isize,
+            options,
             out: String::new(),
-            space: MARGIN,
+            sink: None,
+            sink_error: false,
+            flushed: 0,
+            source_map: None,
             buf: RingBuffer::new(),
             left_total: 0,
             right_total: 0,
@@ -89,15 +151,127 @@ impl Printer {
             print_stack: Vec::new(),
             indent: 0,
             pending_indentation: 0,
+            pending_line_start: false,
+            error: None,
+            fallbacks: Vec::new(),
+            #[cfg(feature = "span-locations")]
+            source: None,
+            #[cfg(feature = "span-locations")]
+            blank_lines: BlankLines::default(),
+            #[cfg(feature = "comments")]
+            comments_before_close: HashMap::new(),
+        }
+    }
+
+    pub fn with_sink(options: Options, sink: &'a mut dyn fmt::Write) -> Self {
+        Printer {
+            sink: Some(sink),
+            ..Printer::new(options)
+        }
+    }
+
+    pub fn eof(mut self) -> Result<String, UnparseError> {
+        self.finish()?;
+        Ok(self.out)
+    }
+
+    pub fn eof_with_source_map(mut self) -> Result<(String, SourceMap), UnparseError> {
+        self.finish()?;
+        let source_map = self.source_map.take().unwrap_or_default();
+        Ok((self.out, source_map))
+    }
+
+    pub fn eof_to_sink(mut self) -> Result<(), WriteError> {
+        self.finish().map_err(WriteError::Unsupported)?;
+        self.flush();
+        if self.sink_error {
+            Err(WriteError::Fmt(fmt::Error))
+        } else {
+            Ok(())
         }
     }
 
-    pub fn eof(mut self) -> String {
+    fn finish(&mut self) -> Result<(), UnparseError> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
         if !self.scan_stack.is_empty() {
             self.check_stack(0);
             self.advance_left();
         }
-        self.out
+        Ok(())
+    }
+
+    fn flush(&mut self) {
+        if let Some(sink) = &mut self.sink {
+            if !self.sink_error && sink.write_str(&self.out).is_err() {
+                self.sink_error = true;
+            }
+            if let Some(source_map) = &mut self.source_map {
+                source_map.count_lines(&self.out, self.flushed);
+            }
+            self.flushed += self.out.len();
+            self.out.clear();
+        }
+    }
+
+    pub fn record_source_map(&mut self) {
+        self.source_map = Some(SourceMap::new());
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    pub fn unsupported(&mut self, error: UnparseError) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+
+    pub fn fallback(&mut self, error: UnparseError) {
+        self.fallbacks.push(error);
+    }
+
+    pub fn take_fallbacks(&mut self) -> Vec<UnparseError> {
+        mem::take(&mut self.fallbacks)
+    }
+
+    #[cfg(feature = "comments")]
+    pub fn set_source(&mut self, source: &'a str) {
+        self.source = Some(source);
+    }
+
+    #[cfg(feature = "span-locations")]
+    pub fn source(&self) -> Option<&'a str> {
+        self.source
+    }
+
+    #[cfg(feature = "span-locations")]
+    pub fn blank_lines(&self) -> &BlankLines {
+        &self.blank_lines
+    }
+
+    #[cfg(feature = "span-locations")]
+    pub fn set_blank_lines(&mut self, blank_lines: BlankLines) {
+        self.blank_lines = blank_lines;
+    }
+
+    #[cfg(feature = "comments")]
+    pub fn set_comments_before_close(&mut self, comments: HashMap<LineColumn, Vec<Attribute>>) {
+        self.comments_before_close = comments;
+    }
+
+    #[cfg(feature = "comments")]
+    pub fn has_comments_at(&self, close: LineColumn) -> bool {
+        self.comments_before_close.contains_key(&close)
+    }
+
+    #[cfg(feature = "comments")]
+    pub fn take_comments_at(&mut self, close: LineColumn) -> Vec<Attribute> {
+        self.comments_before_close
+            .remove(&close)
+            .unwrap_or_default()
     }
 
     pub fn scan_begin(&mut self, token: BeginToken) {
@@ -160,13 +334,13 @@ impl Printer {
         self.right_total += token.blank_space as isize;
     }
 
-    pub fn scan_string(&mut self, string: Cow<'static, str>) {
+    pub fn scan_string(&mut self, string: Cow<'static, str>, span: Option<Span>) {
         if self.scan_stack.is_empty() {
-            self.print_string(string);
+            self.print_string(string, span);
         } else {
-            let len = string.len() as isize;
+            let len = self.str_width(&string);
             self.buf.push(BufEntry {
-                token: Token::String(string),
+                token: Token::String(string, span),
                 size: len,
             });
             self.right_total += len;
@@ -178,7 +352,7 @@ impl Printer {
         match &mut self.buf.last_mut().token {
             Token::Break(token) => token.offset += offset,
             Token::Begin(_) => {}
-            Token::String(_) | Token::End => unreachable!(),
+            Token::String(..) | Token::End => unreachable!(),
         }
     }
 
@@ -194,7 +368,7 @@ impl Printer {
                             let actual_width = entry.size + self.right_total;
                             if actual_width > max {
                                 self.buf.push(BufEntry {
-                                    token: Token::String(Cow::Borrowed("")),
+                                    token: Token::String(Cow::Borrowed(""), None),
                                     size: SIZE_INFINITY,
                                 });
                                 self.right_total += SIZE_INFINITY;
@@ -205,7 +379,7 @@ impl Printer {
                 }
                 Token::End => depth += 1,
                 Token::Break(_) => {}
-                Token::String(_) => unreachable!(),
+                Token::String(..) => unreachable!(),
             }
         }
         self.scan_end();
@@ -231,9 +405,9 @@ impl Printer {
             let left = self.buf.pop_first();
 
             match left.token {
-                Token::String(string) => {
+                Token::String(string, span) => {
                     self.left_total += left.size;
-                    self.print_string(string);
+                    self.print_string(string, span);
                 }
                 Token::Break(token) => {
                     self.left_total += token.blank_space as isize;
@@ -273,7 +447,7 @@ impl Printer {
                         break;
                     }
                 }
-                Token::String(_) => unreachable!(),
+                Token::String(..) => unreachable!(),
             }
         }
     }
@@ -299,10 +473,11 @@ impl Printer {
                     }));
             }
         }
-        if size > self.space {
+        if size > self.space || size > token.max_width {
             self.print_stack
                 .push(PrintFrame::Broken(self.indent, token.breaks));
-            self.indent = usize::try_from(self.indent as isize + token.offset).unwrap();
+            let offset = self.columns(token.offset);
+            self.indent = usize::try_from(self.indent as isize + offset).unwrap();
         } else {
             self.print_stack.push(PrintFrame::Fits(token.breaks));
         }
@@ -349,28 +524,68 @@ impl Printer {
             if cfg!(prettyplease_debug) {
                 self.out.push('·');
             }
-            self.out.push('\n');
-            let indent = self.indent as isize + token.offset;
+            self.out.push_str(self.options.newline_style.as_str());
+            if self.out.len() >= FLUSH_THRESHOLD {
+                self.flush();
+            }
+            let indent = self.indent as isize + self.columns(token.offset);
             self.pending_indentation = usize::try_from(indent).unwrap();
-            self.space = cmp::max(MARGIN - indent, MIN_SPACE);
+            self.pending_line_start = true;
+            self.space = cmp::max(
+                self.options.max_width as isize - indent,
+                self.options.min_space as isize,
+            );
             if let Some(post_break) = token.post_break {
                 self.print_indent();
-                self.out.push(post_break);
-                self.space -= post_break.len_utf8() as isize;
+                self.out.push_str(post_break);
+                self.space -= self.str_width(post_break);
             }
         }
     }
 
-    fn print_string(&mut self, string: Cow<'static, str>) {
+    fn print_string(&mut self, string: Cow<'static, str>, span: Option<Span>) {
         self.print_indent();
+        let width = self.str_width(&string);
+        let newline = self.options.newline_style.as_str();
+        let string = if newline != "\n" && string.contains('\n') {
+            Cow::Owned(replace_newlines(&string, newline))
+        } else {
+            string
+        };
+        if let (Some(source_map), Some(span)) = (&mut self.source_map, span) {
+            source_map.record(&self.out, self.flushed, span, string.len());
+        }
         self.out.push_str(&string);
-        self.space -= string.len() as isize;
+        self.space -= width;
+    }
+
+    fn str_width(&self, string: &str) -> isize {
+        let width = (self.options.str_width.0)(string) as isize;
+        // Measure a \r\n line ending inside the string the same as \n, so the
+        // layout does not depend on which one the string contains.
+        if string.contains('\r') {
+            width - string.matches("\r\n").count() as isize
+        } else {
+            width
+        }
+    }
+
+    // Convert an indentation offset from the token stream, which is expressed
+    // in multiples of INDENT, into the configured number of columns.
+    fn columns(&self, offset: isize) -> isize {
+        offset * self.options.tab_spaces as isize / INDENT
     }
 
     fn print_indent(&mut self) {
-        self.out.reserve(self.pending_indentation);
-        self.out
-            .extend(iter::repeat(' ').take(self.pending_indentation));
+        let mut spaces = self.pending_indentation;
+        if self.options.hard_tabs && self.pending_line_start && self.options.tab_spaces > 0 {
+            let tabs = spaces / self.options.tab_spaces;
+            spaces %= self.options.tab_spaces;
+            self.out.extend(iter::repeat('\t').take(tabs));
+        }
+        self.out.reserve(spaces);
+        self.out.extend(iter::repeat(' ').take(spaces));
         self.pending_indentation = 0;
+        self.pending_line_start = false;
     }
 }
@@ -1,41 +1,74 @@
-use crate::algorithm::Printer;
+use crate::algorithm::{BreakToken, Printer};
+use crate::fixup::FixupContext;
+use crate::iter::IterDelimited;
 use crate::path::PathKind;
 use crate::INDENT;
-use proc_macro2::{Delimiter, Group, TokenStream, TokenTree};
-use syn::{AttrStyle, Attribute, Expr, Lit, MacroDelimiter, Meta, MetaList, MetaNameValue};
+use proc_macro2::{Delimiter, Group, Literal, Spacing, TokenStream, TokenTree};
+use syn::{
+    token, AttrStyle, Attribute, Expr, Lit, MacroDelimiter, Meta, MetaList, MetaNameValue,
+};
 
-impl Printer {
+impl Printer<'_> {
     pub fn outer_attrs(&mut self, attrs: &[Attribute]) {
+        let mut doc_lines = DocLines::default();
         for attr in attrs {
             if let AttrStyle::Outer = attr.style {
-                self.attr(attr);
+                self.attr(attr, &mut doc_lines);
             }
         }
+        self.end_paragraph(&mut doc_lines);
+    }
+
+    // Print the comments that followed the last node inside of these braces in
+    // the original source, ahead of the closing brace.
+    #[cfg(feature = "comments")]
+    pub fn comments_before_close(&mut self, brace: &token::Brace) {
+        let comments = self.take_comments_at(brace.span.close().start());
+        self.outer_attrs(&comments);
+    }
+
+    #[cfg(not(feature = "comments"))]
+    pub fn comments_before_close(&mut self, _brace: &token::Brace) {}
+
+    #[cfg(feature = "comments")]
+    pub fn has_comments_before_close(&self, brace: &token::Brace) -> bool {
+        self.has_comments_at(brace.span.close().start())
+    }
+
+    #[cfg(not(feature = "comments"))]
+    pub fn has_comments_before_close(&self, _brace: &token::Brace) -> bool {
+        false
     }
 
     pub fn inner_attrs(&mut self, attrs: &[Attribute]) {
+        let mut doc_lines = DocLines::default();
         for attr in attrs {
             if let AttrStyle::Inner(_) = attr.style {
-                self.attr(attr);
+                self.attr(attr, &mut doc_lines);
             }
         }
+        self.end_paragraph(&mut doc_lines);
     }
 
-    fn attr(&mut self, attr: &Attribute) {
+    fn attr(&mut self, attr: &Attribute, doc_lines: &mut DocLines) {
         if let Some(mut doc) = value_of_attribute("doc", attr) {
+            let marker = match attr.style {
+                AttrStyle::Outer => "///",
+                AttrStyle::Inner(_) => "//!",
+            };
             if !doc.contains('\n')
                 && match attr.style {
                     AttrStyle::Outer => !doc.starts_with('/'),
                     AttrStyle::Inner(_) => true,
                 }
             {
-                trim_trailing_spaces(&mut doc);
-                self.word(match attr.style {
-                    AttrStyle::Outer => "///",
-                    AttrStyle::Inner(_) => "//!",
-                });
-                self.word(doc);
-                self.hardbreak();
+                self.doc_line(marker, doc, doc_lines);
+                return;
+            } else if self.options().normalize_doc_attributes && can_be_doc_lines(&doc, &attr.style)
+            {
+                for line in doc.split('\n') {
+                    self.doc_line(marker, line.to_owned(), doc_lines);
+                }
                 return;
             } else if can_be_block_comment(&doc)
                 && match attr.style {
@@ -43,6 +76,7 @@ impl Printer {
                     AttrStyle::Inner(_) => true,
                 }
             {
+                self.end_paragraph(doc_lines);
                 trim_interior_trailing_spaces(&mut doc);
                 self.word(match attr.style {
                     AttrStyle::Outer => "/**",
@@ -54,6 +88,7 @@ impl Printer {
                 return;
             }
         } else if let Some(mut comment) = value_of_attribute("comment", attr) {
+            self.end_paragraph(doc_lines);
             if !comment.contains('\n') {
                 trim_trailing_spaces(&mut comment);
                 self.word("//");
@@ -70,6 +105,7 @@ impl Printer {
             }
         }
 
+        self.end_paragraph(doc_lines);
         self.word(match attr.style {
             AttrStyle::Outer => "#",
             AttrStyle::Inner(_) => "#!",
@@ -80,6 +116,103 @@ impl Printer {
         self.space();
     }
 
+    fn doc_line(&mut self, marker: &'static str, mut doc: String, doc_lines: &mut DocLines) {
+        trim_trailing_spaces(&mut doc);
+        if !self.options().wrap_doc_comments {
+            self.word(marker);
+            self.word(doc);
+            self.hardbreak();
+            return;
+        }
+        if let Some((_, paragraph)) = &mut doc_lines.paragraph {
+            if continues_paragraph(paragraph, &doc) {
+                paragraph.push(' ');
+                paragraph.push_str(doc.trim_start_matches(' '));
+                return;
+            }
+        }
+        self.end_paragraph(doc_lines);
+        if !doc_lines.in_code_block
+            && !doc.trim_start_matches(' ').is_empty()
+            && wrap_indent(&doc).is_some()
+        {
+            doc_lines.paragraph = Some((marker, doc));
+        } else {
+            self.doc_wrapped(marker, &doc, &mut doc_lines.in_code_block);
+            self.hardbreak();
+        }
+    }
+
+    // Print the lines of prose collected so far, joined up and wrapped anew.
+    fn end_paragraph(&mut self, doc_lines: &mut DocLines) {
+        if let Some((marker, paragraph)) = doc_lines.paragraph.take() {
+            self.doc_wrapped(marker, &paragraph, &mut doc_lines.in_code_block);
+            self.hardbreak();
+        }
+    }
+
+    // Print one line or joined paragraph of a doc comment, letting the printer
+    // break it into several lines at spaces where it would not fit. Runs of
+    // spaces between words become one, as they do in rendered markdown. Lines
+    // of code blocks, headings, tables and the like are printed as they are.
+    fn doc_wrapped(&mut self, marker: &'static str, doc: &str, in_code_block: &mut bool) {
+        let content = doc.trim_start_matches(' ');
+        if content.starts_with("```") || content.starts_with("~~~") {
+            *in_code_block = !*in_code_block;
+            self.word(marker);
+            self.word(doc.to_owned());
+            return;
+        }
+        let continuation = match wrap_indent(doc) {
+            Some(indent) if !*in_code_block => continuation_prefix(marker, indent),
+            _ => None,
+        };
+        let continuation = match continuation {
+            Some(continuation) => continuation,
+            None => {
+                self.word(marker);
+                self.word(doc.to_owned());
+                return;
+            }
+        };
+
+        self.word(marker);
+        self.ibox(0);
+        let first_word = doc.len() - content.len() + list_marker_len(content);
+        let mut chunk = doc[..first_word].to_owned();
+        let mut in_code_span = false;
+        let mut link_depth = 0;
+        for (i, word) in doc[first_word..].split(' ').enumerate() {
+            if word.is_empty() && i > 0 {
+                chunk.push(' ');
+                continue;
+            }
+            let gap = chunk.len() - chunk.trim_end_matches(' ').len();
+            if i > 0 && gap > 0 && !in_code_span && link_depth == 0 && !starts_block(word) {
+                chunk.truncate(chunk.len() - gap);
+                self.word(chunk);
+                self.scan_break(BreakToken {
+                    blank_space: 1,
+                    post_break: Some(continuation),
+                    ..BreakToken::default()
+                });
+                chunk = String::new();
+            }
+            chunk.push_str(word);
+            chunk.push(' ');
+            if word.matches('`').count() % 2 == 1 {
+                in_code_span = !in_code_span;
+            }
+            if !in_code_span {
+                link_depth += word.matches('[').count();
+                link_depth = link_depth.saturating_sub(word.matches(']').count());
+            }
+        }
+        chunk.pop();
+        self.word(chunk);
+        self.end();
+    }
+
     fn meta(&mut self, meta: &Meta) {
         match meta {
             Meta::Path(path) => self.path(path, PathKind::Simple),
@@ -90,6 +223,13 @@ impl Printer {
 
     fn meta_list(&mut self, meta: &MetaList) {
         self.path(&meta.path, PathKind::Simple);
+        if let MacroDelimiter::Paren(_) = meta.delimiter {
+            if let Some(nested) = parse_nested_meta(meta.tokens.clone()) {
+                let trailing_comma = nested.trailing_comma || meta.path.is_ident("derive");
+                self.nested_meta_list(&nested.items, trailing_comma);
+                return;
+            }
+        }
         let delimiter = match meta.delimiter {
             MacroDelimiter::Paren(_) => Delimiter::Parenthesis,
             MacroDelimiter::Brace(_) => Delimiter::Brace,
@@ -99,10 +239,55 @@ impl Printer {
         self.attr_tokens(TokenStream::from(TokenTree::Group(group)));
     }
 
+    // Print the arguments of an attribute like `derive(...)` or `cfg(...)`
+    // that consist of paths, nested lists, `name = value` pairs and literals,
+    // one per line if they do not fit on one. A lone nested list is printed
+    // without a line break of its own, as in `cfg(all(...))`.
+    fn nested_meta_list(&mut self, items: &[NestedMeta], trailing_comma: bool) {
+        self.word("(");
+        if let [NestedMeta::List(path, nested)] = items {
+            self.attr_tokens(path.clone());
+            self.nested_meta_list(&nested.items, nested.trailing_comma || is_derive(path));
+        } else {
+            self.cbox(INDENT);
+            self.zerobreak();
+            for item in items.iter().delimited() {
+                self.nested_meta(&item);
+                if trailing_comma {
+                    self.trailing_comma(item.is_last);
+                } else if item.is_last {
+                    self.zerobreak();
+                } else {
+                    self.word(",");
+                    self.space();
+                }
+            }
+            self.offset(-INDENT);
+            self.end();
+        }
+        self.word(")");
+    }
+
+    fn nested_meta(&mut self, item: &NestedMeta) {
+        match item {
+            NestedMeta::Path(path) => self.attr_tokens(path.clone()),
+            NestedMeta::List(path, nested) => {
+                self.attr_tokens(path.clone());
+                self.nested_meta_list(&nested.items, nested.trailing_comma || is_derive(path));
+            }
+            NestedMeta::NameValue(path, value) => {
+                self.attr_tokens(path.clone());
+                self.word(" = ");
+                self.attr_tokens(value.clone());
+            }
+            NestedMeta::Lit(lit) => self.token_literal(lit),
+        }
+    }
+
     fn meta_name_value(&mut self, meta: &MetaNameValue) {
         self.path(&meta.path, PathKind::Simple);
         self.word(" = ");
-        self.expr(&meta.value);
+        self.expr(&meta.value, FixupContext::NONE);
     }
 
     fn attr_tokens(&mut self, tokens: TokenStream) {
@@ -118,7 +303,7 @@ impl Printer {
         }
 
         use State::*;
-        let mut state = Word;
+        let mut state = Punct;
 
         while let Some((tokens, delimiter)) = stack.last_mut() {
             match tokens.next() {
@@ -208,6 +393,101 @@ impl Printer {
     }
 }
 
+struct NestedMetaList {
+    items: Vec<NestedMeta>,
+    trailing_comma: bool,
+}
+
+enum NestedMeta {
+    Path(TokenStream),
+    List(TokenStream, NestedMetaList),
+    NameValue(TokenStream, TokenStream),
+    Lit(Literal),
+}
+
+// Recognize the comma separated arguments of an attribute, or None if they are
+// anything other than paths, lists, `name = value` pairs and literals.
+fn parse_nested_meta(tokens: TokenStream) -> Option<NestedMetaList> {
+    let mut items = Vec::new();
+    let mut trailing_comma = false;
+    let mut tokens = tokens.into_iter().peekable();
+    while tokens.peek().is_some() {
+        let mut element = Vec::new();
+        trailing_comma = false;
+        for token in &mut tokens {
+            if let TokenTree::Punct(punct) = &token {
+                if punct.as_char() == ',' {
+                    trailing_comma = true;
+                    break;
+                }
+            }
+            element.push(token);
+        }
+        items.push(parse_meta_element(element)?);
+    }
+    if items.is_empty() {
+        return None;
+    }
+    Some(NestedMetaList {
+        items,
+        trailing_comma,
+    })
+}
+
+fn parse_meta_element(mut element: Vec<TokenTree>) -> Option<NestedMeta> {
+    if let [TokenTree::Literal(lit)] = element.as_slice() {
+        return Some(NestedMeta::Lit(lit.clone()));
+    }
+    let path_len = path_len(&element);
+    if path_len == 0 {
+        return None;
+    }
+    let rest = element.split_off(path_len);
+    let path = element.into_iter().collect();
+    match rest.as_slice() {
+        [] => Some(NestedMeta::Path(path)),
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::Parenthesis => {
+            let nested = parse_nested_meta(group.stream())?;
+            Some(NestedMeta::List(path, nested))
+        }
+        [TokenTree::Punct(eq), _, ..] if eq.as_char() == '=' && eq.spacing() == Spacing::Alone => {
+            let value = rest[1..].iter().cloned().collect();
+            Some(NestedMeta::NameValue(path, value))
+        }
+        _ => None,
+    }
+}
+
+// Number of tokens at the beginning that make up a path like `a::b`.
+fn path_len(tokens: &[TokenTree]) -> usize {
+    let mut len = 0;
+    loop {
+        let colons = match &tokens[len..] {
+            [TokenTree::Punct(first), TokenTree::Punct(second), ..]
+                if first.as_char() == ':'
+                    && first.spacing() == Spacing::Joint
+                    && second.as_char() == ':' =>
+            {
+                2
+            }
+            _ if len == 0 => 0,
+            _ => return len,
+        };
+        match tokens.get(len + colons) {
+            Some(TokenTree::Ident(_)) => len += colons + 1,
+            _ => return 0,
+        }
+    }
+}
+
+fn is_derive(path: &TokenStream) -> bool {
+    let mut tokens = path.clone().into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(ident)), None) => ident == "derive",
+        _ => false,
+    }
+}
+
 fn value_of_attribute(requested: &str, attr: &Attribute) -> Option<String> {
     let value = match &attr.meta {
         Meta::NameValue(meta) if meta.path.is_ident(requested) => &meta.value,
@@ -223,6 +503,18 @@ fn value_of_attribute(requested: &str, attr: &Attribute) -> Option<String> {
     }
 }
 
+// Whether the attribute is a `#[comment = "..."]` pseudo-attribute, which is
+// printed as a comment rather than as an attribute.
+pub fn is_comment(attr: &Attribute) -> bool {
+    value_of_attribute("comment", attr).is_some()
+}
+
+// Whether there are no attributes other than comments, i.e. nothing that would
+// be printed in front of the expression and bind to it.
+pub fn only_comments(attrs: &[Attribute]) -> bool {
+    attrs.iter().all(is_comment)
+}
+
 pub fn has_outer(attrs: &[Attribute]) -> bool {
     for attr in attrs {
         if let AttrStyle::Outer = attr.style {
@@ -241,6 +533,97 @@ pub fn has_inner(attrs: &[Attribute]) -> bool {
     false
 }
 
+#[derive(Default)]
+struct DocLines {
+    in_code_block: bool,
+    // A paragraph of doc comment lines waiting to be printed, while the lines
+    // that follow it may still be joined onto it.
+    paragraph: Option<(&'static str, String)>,
+}
+
+// Whether a doc comment line carries on the paragraph before it, so that the
+// two can be joined into one line without markdown reading them differently.
+fn continues_paragraph(paragraph: &str, doc: &str) -> bool {
+    let content = doc.trim_start_matches(' ');
+    let indent = doc.len() - content.len();
+    let first_word = content.split(' ').next().unwrap();
+    !paragraph.ends_with('\\')
+        && !content.is_empty()
+        && (indent <= 4 || wrap_indent(paragraph).map_or(false, |wrap| indent <= wrap))
+        && !content.starts_with('\t')
+        && !starts_block(first_word)
+}
+
+// Column within a doc comment line at which the lines it is wrapped into
+// continue, or None if markdown would read the line differently when split.
+fn wrap_indent(doc: &str) -> Option<usize> {
+    let content = doc.trim_start_matches(' ');
+    let indent = doc.len() - content.len();
+    // Besides the customary space after `///`, 4 spaces of indentation make
+    // the line a code block.
+    if indent > 4 || content.starts_with('\t') || starts_block(content) {
+        return None;
+    }
+    Some(indent + list_marker_len(content))
+}
+
+// Length of the bullet or number at the beginning of a list item, including
+// the spaces after it.
+fn list_marker_len(content: &str) -> usize {
+    let marker = content.split(' ').next().unwrap();
+    let text = content[marker.len()..].trim_start_matches(' ');
+    if is_list_marker(marker) && marker.len() < content.len() && !text.is_empty() {
+        content.len() - text.len()
+    } else {
+        0
+    }
+}
+
+fn is_list_marker(word: &str) -> bool {
+    match word {
+        "-" | "*" | "+" => true,
+        _ => match word.strip_suffix(&['.', ')'][..]) {
+            Some(digits) => {
+                (1..10).contains(&digits.len()) && digits.bytes().all(|b| b.is_ascii_digit())
+            }
+            None => false,
+        },
+    }
+}
+
+// Whether a line beginning with this word is something other than a paragraph
+// to markdown, such as a heading, list item or table row, so a wrapped line
+// must not begin with it.
+fn starts_block(word: &str) -> bool {
+    word.starts_with(&['#', '>', '|', '<'][..])
+        || word.starts_with("```")
+        || word.starts_with("~~~")
+        || word.starts_with('[') && word.contains("]:")
+        || !word.is_empty() && word.bytes().all(|b| b"-*+=_".contains(&b))
+        || is_list_marker(word)
+}
+
+// The text printed at the start of a line that a doc comment line continues
+// on: the `///` followed by the indentation of the continued text.
+fn continuation_prefix(marker: &str, indent: usize) -> Option<&'static str> {
+    const OUTER: &str = "///                                        ";
+    const INNER: &str = "//!                                        ";
+    let prefix = if marker == "///" { OUTER } else { INNER };
+    prefix.get(..marker.len() + indent)
+}
+
+// Whether a doc string can be printed as `///` or `//!` lines, one for each
+// line of the string, without changing its value. A \r would not survive as
+// part of a line comment, and an outer doc line starting with `/` would turn
+// into a `////` comment, which is not a doc comment.
+fn can_be_doc_lines(doc: &str, style: &AttrStyle) -> bool {
+    !doc.contains('\r')
+        && match style {
+            AttrStyle::Outer => doc.split('\n').all(|line| !line.starts_with('/')),
+            AttrStyle::Inner(_) => true,
+        }
+}
+
 fn trim_trailing_spaces(doc: &mut String) {
     doc.truncate(doc.trim_end_matches(' ').len());
 }
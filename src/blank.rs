@@ -0,0 +1,585 @@
+use crate::algorithm::Printer;
+
+#[cfg(feature = "span-locations")]
+pub use self::spans::BlankLines;
+
+impl Printer<'_> {
+    // Reproduce the blank lines that preceded this node in the original
+    // source, up to Options::blank_lines of them.
+    #[cfg(feature = "span-locations")]
+    pub fn blank_lines_before<T>(&mut self, node: &T) {
+        for _ in 0..self.blank_lines().get(node) {
+            self.hardbreak();
+        }
+    }
+
+    #[cfg(not(feature = "span-locations"))]
+    pub fn blank_lines_before<T>(&mut self, _node: &T) {}
+
+    #[cfg(feature = "span-locations")]
+    pub fn has_blank_lines_before<T>(&self, node: &T) -> bool {
+        self.blank_lines().get(node) > 0
+    }
+
+    #[cfg(not(feature = "span-locations"))]
+    pub fn has_blank_lines_before<T>(&self, _node: &T) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "span-locations")]
+mod spans {
+    use crate::attr;
+    use proc_macro2::{Delimiter, Ident, Span, TokenTree};
+    use quote::ToTokens;
+    use std::collections::HashMap;
+    use syn::spanned::Spanned;
+    use syn::visit::{self, Visit};
+    use syn::{
+        AttrStyle, Attribute, Block, Expr, Field, Fields, FieldsNamed, File, ForeignItem, ImplItem,
+        Item, ItemEnum, ItemForeignMod, ItemImpl, ItemMod, ItemTrait, MacroDelimiter, Meta,
+        Signature, Stmt, TraitItem, Variant, Visibility,
+    };
+
+    // Number of blank lines to print ahead of each node, keyed by the node's
+    // address in the syntax tree being printed.
+    #[derive(Default)]
+    pub struct BlankLines {
+        before: HashMap<usize, usize>,
+    }
+
+    impl BlankLines {
+        // Measure the gaps between consecutive items, impl members, struct
+        // fields, enum variants and statements using the line information in
+        // their spans. If the source text is available, only lines that are
+        // empty there count, otherwise any line not covered by a node does,
+        // such as a line holding a comment that syn discarded.
+        pub fn new(file: &File, source: Option<&str>, max: usize) -> Self {
+            let mut collect = Collect {
+                lines: source.map(|source| source.lines().collect()),
+                max,
+                blank_lines: BlankLines::default(),
+            };
+            collect.visit_file(file);
+            collect.blank_lines
+        }
+
+        pub fn get<T>(&self, node: &T) -> usize {
+            let address = node as *const T as usize;
+            self.before.get(&address).copied().unwrap_or(0)
+        }
+    }
+
+    struct Collect<'a> {
+        lines: Option<Vec<&'a str>>,
+        max: usize,
+        blank_lines: BlankLines,
+    }
+
+    impl Collect<'_> {
+        fn sequence<'a, T: Extent + 'a>(&mut self, nodes: impl IntoIterator<Item = &'a T>) {
+            let mut prev_end = None;
+            for node in nodes {
+                let (start, end) = match extent(node) {
+                    Some(extent) => extent,
+                    None => continue,
+                };
+                if let Some(prev_end) = prev_end {
+                    let count = self.count_blank_lines(prev_end, start);
+                    if count > 0 {
+                        let address = node as *const T as usize;
+                        self.blank_lines.before.insert(address, count);
+                    }
+                }
+                prev_end = Some(end);
+            }
+        }
+
+        // Blank lines strictly between two 1-based line numbers.
+        fn count_blank_lines(&self, prev_end: usize, next_start: usize) -> usize {
+            let between = prev_end + 1..next_start;
+            let count = match &self.lines {
+                Some(lines) => between
+                    .filter(|line| lines.get(line - 1).map_or(false, |s| s.trim().is_empty()))
+                    .count(),
+                None => between.len(),
+            };
+            count.min(self.max)
+        }
+    }
+
+    // First and last line of a node. Only the tokens at either end of the node
+    // are looked at where possible, because turning every node back into
+    // tokens would take time quadratic in how deeply the nodes are nested.
+    fn extent<T: Extent>(node: &T) -> Option<(usize, usize)> {
+        let (first, last) = node.ends()?;
+        Some((first.start().line, last.end().line))
+    }
+
+    // Spans of the first and last token of a node. Pseudo-attributes for
+    // comments that were attached to the node after parsing carry no location,
+    // so those are skipped.
+    pub trait Extent {
+        fn ends(&self) -> Option<(Span, Span)>;
+    }
+
+    impl Extent for Item {
+        fn ends(&self) -> Option<(Span, Span)> {
+            let (attrs, vis, first, last) = match self {
+                Item::Const(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.const_token.span,
+                    item.semi_token.span(),
+                ),
+                Item::Enum(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.enum_token.span,
+                    item.brace_token.span.close(),
+                ),
+                Item::ExternCrate(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.extern_token.span,
+                    item.semi_token.span(),
+                ),
+                Item::Fn(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    signature_first(&item.sig),
+                    item.block.brace_token.span.close(),
+                ),
+                Item::ForeignMod(item) => (
+                    &item.attrs,
+                    None,
+                    item.abi.extern_token.span,
+                    item.brace_token.span.close(),
+                ),
+                Item::Impl(item) => (
+                    &item.attrs,
+                    None,
+                    first_of(&[
+                        item.defaultness.as_ref().map(|token| token.span),
+                        item.unsafety.as_ref().map(|token| token.span),
+                    ])
+                    .unwrap_or(item.impl_token.span),
+                    item.brace_token.span.close(),
+                ),
+                Item::Macro(item) => (
+                    &item.attrs,
+                    None,
+                    item.mac.path.span(),
+                    match &item.semi_token {
+                        Some(semi_token) => semi_token.span(),
+                        None => delimiter_close(&item.mac.delimiter),
+                    },
+                ),
+                Item::Mod(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.unsafety
+                        .as_ref()
+                        .map_or(item.mod_token.span, |token| token.span),
+                    match (&item.content, &item.semi) {
+                        (Some((brace_token, _items)), _) => brace_token.span.close(),
+                        (None, Some(semi_token)) => semi_token.span(),
+                        (None, None) => item.ident.span(),
+                    },
+                ),
+                Item::Static(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.static_token.span,
+                    item.semi_token.span(),
+                ),
+                Item::Struct(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.struct_token.span,
+                    match (&item.semi_token, &item.fields) {
+                        (Some(semi_token), _) => semi_token.span(),
+                        (None, Fields::Named(fields)) => fields.brace_token.span.close(),
+                        (None, Fields::Unnamed(fields)) => fields.paren_token.span.close(),
+                        (None, Fields::Unit) => item.ident.span(),
+                    },
+                ),
+                Item::Trait(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    first_of(&[
+                        item.unsafety.as_ref().map(|token| token.span),
+                        item.auto_token.as_ref().map(|token| token.span),
+                    ])
+                    .unwrap_or(item.trait_token.span),
+                    item.brace_token.span.close(),
+                ),
+                Item::TraitAlias(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.trait_token.span,
+                    item.semi_token.span(),
+                ),
+                Item::Type(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.type_token.span,
+                    item.semi_token.span(),
+                ),
+                Item::Union(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.union_token.span,
+                    item.fields.brace_token.span.close(),
+                ),
+                Item::Use(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.use_token.span,
+                    item.semi_token.span(),
+                ),
+                _ => return token_ends(self),
+            };
+            Some(ends(attrs, vis, first, last))
+        }
+    }
+
+    impl Extent for ImplItem {
+        fn ends(&self) -> Option<(Span, Span)> {
+            let (attrs, vis, first, last) = match self {
+                ImplItem::Const(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.defaultness
+                        .as_ref()
+                        .map_or(item.const_token.span, |token| token.span),
+                    item.semi_token.span(),
+                ),
+                ImplItem::Fn(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.defaultness
+                        .as_ref()
+                        .map_or_else(|| signature_first(&item.sig), |token| token.span),
+                    item.block.brace_token.span.close(),
+                ),
+                ImplItem::Type(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.defaultness
+                        .as_ref()
+                        .map_or(item.type_token.span, |token| token.span),
+                    item.semi_token.span(),
+                ),
+                ImplItem::Macro(item) => (
+                    &item.attrs,
+                    None,
+                    item.mac.path.span(),
+                    match &item.semi_token {
+                        Some(semi_token) => semi_token.span(),
+                        None => delimiter_close(&item.mac.delimiter),
+                    },
+                ),
+                _ => return token_ends(self),
+            };
+            Some(ends(attrs, vis, first, last))
+        }
+    }
+
+    impl Extent for TraitItem {
+        fn ends(&self) -> Option<(Span, Span)> {
+            let (attrs, first, last) = match self {
+                TraitItem::Const(item) => {
+                    (&item.attrs, item.const_token.span, item.semi_token.span())
+                }
+                TraitItem::Fn(item) => (
+                    &item.attrs,
+                    signature_first(&item.sig),
+                    match (&item.default, &item.semi_token) {
+                        (Some(block), _) => block.brace_token.span.close(),
+                        (None, Some(semi_token)) => semi_token.span(),
+                        (None, None) => item.sig.fn_token.span,
+                    },
+                ),
+                TraitItem::Type(item) => {
+                    (&item.attrs, item.type_token.span, item.semi_token.span())
+                }
+                TraitItem::Macro(item) => (
+                    &item.attrs,
+                    item.mac.path.span(),
+                    match &item.semi_token {
+                        Some(semi_token) => semi_token.span(),
+                        None => delimiter_close(&item.mac.delimiter),
+                    },
+                ),
+                _ => return token_ends(self),
+            };
+            Some(ends(attrs, None, first, last))
+        }
+    }
+
+    impl Extent for ForeignItem {
+        fn ends(&self) -> Option<(Span, Span)> {
+            let (attrs, vis, first, last) = match self {
+                ForeignItem::Fn(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    signature_first(&item.sig),
+                    item.semi_token.span(),
+                ),
+                ForeignItem::Static(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.static_token.span,
+                    item.semi_token.span(),
+                ),
+                ForeignItem::Type(item) => (
+                    &item.attrs,
+                    Some(&item.vis),
+                    item.type_token.span,
+                    item.semi_token.span(),
+                ),
+                ForeignItem::Macro(item) => (
+                    &item.attrs,
+                    None,
+                    item.mac.path.span(),
+                    match &item.semi_token {
+                        Some(semi_token) => semi_token.span(),
+                        None => delimiter_close(&item.mac.delimiter),
+                    },
+                ),
+                _ => return token_ends(self),
+            };
+            Some(ends(attrs, vis, first, last))
+        }
+    }
+
+    impl Extent for Variant {
+        fn ends(&self) -> Option<(Span, Span)> {
+            let last = match (&self.discriminant, &self.fields) {
+                (Some((_eq_token, discriminant)), _) => token_ends(discriminant)?.1,
+                (None, Fields::Named(fields)) => fields.brace_token.span.close(),
+                (None, Fields::Unnamed(fields)) => fields.paren_token.span.close(),
+                (None, Fields::Unit) => self.ident.span(),
+            };
+            Some(ends(&self.attrs, None, self.ident.span(), last))
+        }
+    }
+
+    impl Extent for Field {
+        fn ends(&self) -> Option<(Span, Span)> {
+            let (ty_first, last) = token_ends(&self.ty)?;
+            let first = self.ident.as_ref().map_or(ty_first, Ident::span);
+            Some(ends(&self.attrs, Some(&self.vis), first, last))
+        }
+    }
+
+    impl Extent for Stmt {
+        fn ends(&self) -> Option<(Span, Span)> {
+            match self {
+                Stmt::Local(local) => Some(ends(
+                    &local.attrs,
+                    None,
+                    local.let_token.span,
+                    local.semi_token.span(),
+                )),
+                Stmt::Item(item) => item.ends(),
+                Stmt::Expr(expr, semi_token) => {
+                    let (first, last) = match block_like_ends(expr) {
+                        Some(ends) => ends,
+                        None => token_ends(expr)?,
+                    };
+                    Some((first, semi_token.as_ref().map_or(last, Spanned::span)))
+                }
+                Stmt::Macro(stmt) => Some(ends(
+                    &stmt.attrs,
+                    None,
+                    stmt.mac.path.span(),
+                    match &stmt.semi_token {
+                        Some(semi_token) => semi_token.span(),
+                        None => delimiter_close(&stmt.mac.delimiter),
+                    },
+                )),
+            }
+        }
+    }
+
+    // Ends of an expression statement that ends in a block, which are the
+    // ones whose contents hold further statements.
+    fn block_like_ends(expr: &Expr) -> Option<(Span, Span)> {
+        let (attrs, label, first, last) = match expr {
+            Expr::Block(expr) => (
+                &expr.attrs,
+                &expr.label,
+                expr.block.brace_token.span.open(),
+                expr.block.brace_token.span.close(),
+            ),
+            Expr::ForLoop(expr) => (
+                &expr.attrs,
+                &expr.label,
+                expr.for_token.span,
+                expr.body.brace_token.span.close(),
+            ),
+            Expr::If(expr) => {
+                let mut last = expr.then_branch.brace_token.span.close();
+                let mut else_branch = &expr.else_branch;
+                while let Some((_else_token, branch)) = else_branch {
+                    match &**branch {
+                        Expr::If(expr) => {
+                            last = expr.then_branch.brace_token.span.close();
+                            else_branch = &expr.else_branch;
+                        }
+                        Expr::Block(expr) => {
+                            last = expr.block.brace_token.span.close();
+                            break;
+                        }
+                        branch => {
+                            last = token_ends(branch)?.1;
+                            break;
+                        }
+                    }
+                }
+                (&expr.attrs, &None, expr.if_token.span, last)
+            }
+            Expr::Loop(expr) => (
+                &expr.attrs,
+                &expr.label,
+                expr.loop_token.span,
+                expr.body.brace_token.span.close(),
+            ),
+            Expr::Match(expr) => (
+                &expr.attrs,
+                &None,
+                expr.match_token.span,
+                expr.brace_token.span.close(),
+            ),
+            Expr::Unsafe(expr) => (
+                &expr.attrs,
+                &None,
+                expr.unsafe_token.span,
+                expr.block.brace_token.span.close(),
+            ),
+            Expr::While(expr) => (
+                &expr.attrs,
+                &expr.label,
+                expr.while_token.span,
+                expr.body.brace_token.span.close(),
+            ),
+            _ => return None,
+        };
+        let first = label.as_ref().map_or(first, |label| label.name.apostrophe);
+        Some(ends(attrs, None, first, last))
+    }
+
+    fn ends(
+        attrs: &[Attribute],
+        vis: Option<&Visibility>,
+        first: Span,
+        last: Span,
+    ) -> (Span, Span) {
+        let attr = attrs
+            .iter()
+            .find(|attr| matches!(attr.style, AttrStyle::Outer) && !attr::is_comment(attr))
+            .map(|attr| attr.pound_token.span());
+        let vis = match vis {
+            Some(Visibility::Public(pub_token)) => Some(pub_token.span),
+            Some(Visibility::Restricted(vis)) => Some(vis.pub_token.span),
+            Some(Visibility::Inherited) | None => None,
+        };
+        (first_of(&[attr, vis]).unwrap_or(first), last)
+    }
+
+    fn signature_first(sig: &Signature) -> Span {
+        first_of(&[
+            sig.constness.as_ref().map(|token| token.span),
+            sig.asyncness.as_ref().map(|token| token.span),
+            sig.unsafety.as_ref().map(|token| token.span),
+            sig.abi.as_ref().map(|abi| abi.extern_token.span),
+        ])
+        .unwrap_or(sig.fn_token.span)
+    }
+
+    fn first_of(spans: &[Option<Span>]) -> Option<Span> {
+        spans.iter().copied().flatten().next()
+    }
+
+    fn delimiter_close(delimiter: &MacroDelimiter) -> Span {
+        match delimiter {
+            MacroDelimiter::Paren(paren) => paren.span.close(),
+            MacroDelimiter::Brace(brace) => brace.span.close(),
+            MacroDelimiter::Bracket(bracket) => bracket.span.close(),
+        }
+    }
+
+    // Ends of a node that has no cheaper way to find them, from its tokens.
+    fn token_ends<T: ToTokens>(node: &T) -> Option<(Span, Span)> {
+        let mut tokens = node.to_token_stream().into_iter().peekable();
+        let first = loop {
+            let token = tokens.next()?;
+            if let TokenTree::Punct(punct) = &token {
+                if punct.as_char() == '#' {
+                    if let Some(TokenTree::Group(group)) = tokens.peek() {
+                        if group.delimiter() == Delimiter::Bracket && is_comment(group.stream()) {
+                            tokens.next();
+                            continue;
+                        }
+                    }
+                }
+            }
+            break token;
+        };
+        let last = tokens.last().unwrap_or_else(|| first.clone());
+        Some((first.span(), last.span()))
+    }
+
+    fn is_comment(tokens: proc_macro2::TokenStream) -> bool {
+        match syn::parse2::<Meta>(tokens) {
+            Ok(Meta::NameValue(meta)) => meta.path.is_ident("comment"),
+            _ => false,
+        }
+    }
+
+    impl<'ast> Visit<'ast> for Collect<'_> {
+        fn visit_file(&mut self, file: &'ast File) {
+            self.sequence(&file.items);
+            visit::visit_file(self, file);
+        }
+
+        fn visit_item_mod(&mut self, item: &'ast ItemMod) {
+            if let Some((_brace, items)) = &item.content {
+                self.sequence(items);
+            }
+            visit::visit_item_mod(self, item);
+        }
+
+        fn visit_item_impl(&mut self, item: &'ast ItemImpl) {
+            self.sequence(&item.items);
+            visit::visit_item_impl(self, item);
+        }
+
+        fn visit_item_trait(&mut self, item: &'ast ItemTrait) {
+            self.sequence(&item.items);
+            visit::visit_item_trait(self, item);
+        }
+
+        fn visit_item_foreign_mod(&mut self, item: &'ast ItemForeignMod) {
+            self.sequence(&item.items);
+            visit::visit_item_foreign_mod(self, item);
+        }
+
+        fn visit_item_enum(&mut self, item: &'ast ItemEnum) {
+            self.sequence(&item.variants);
+            visit::visit_item_enum(self, item);
+        }
+
+        fn visit_fields_named(&mut self, fields: &'ast FieldsNamed) {
+            self.sequence(&fields.named);
+            visit::visit_fields_named(self, fields);
+        }
+
+        fn visit_block(&mut self, block: &'ast Block) {
+            self.sequence(&block.stmts);
+            visit::visit_block(self, block);
+        }
+    }
+}
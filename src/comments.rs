@@ -0,0 +1,603 @@
+use proc_macro2::{LineColumn, Span};
+use std::collections::HashMap;
+use std::mem;
+use syn::spanned::Spanned;
+use syn::token;
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    Arm, AttrStyle, Attribute, Block, Expr, ExprLit, ExprMatch, Field, FieldsNamed, File,
+    ForeignItem, Ident, ImplItem, Item, ItemEnum, ItemForeignMod, ItemImpl, ItemMod, ItemTrait,
+    Lit, LitStr, Local, Meta, MetaNameValue, Path, Stmt, StmtMacro, TraitItem, Variant,
+};
+
+// Comments that follow the last node of the file or of a block or body, which
+// have no node to be attached to.
+#[derive(Default)]
+pub struct Trailing {
+    // Comments after the last node in the file, to be printed following it
+    pub file: Vec<Attribute>,
+    // Comments after the last node inside of braces, to be printed ahead of
+    // the closing brace, keyed by the position where the brace starts
+    pub braces: HashMap<LineColumn, Vec<Attribute>>,
+}
+
+// Reattach the `//` and `/* */` comments found in `source`, which syn drops
+// during parsing, to the syntax tree parsed from it. Each comment turns into a
+// `#[comment = "..."]` pseudo-attribute on the nearest item, statement, field,
+// variant or match arm within the same block or body, which is printed back as
+// a comment by attr.rs. Comments after the last such node in their block or
+// body, or in the file, are returned in the same form.
+pub fn attach(file: &mut File, source: &str) -> Trailing {
+    let comments = scan(source);
+    let mut trailing = Trailing::default();
+
+    let mut walk = Walk {
+        pass: Pass::Collect(Collect::default()),
+    };
+    walk.visit_file_mut(file);
+    let collect = match walk.pass {
+        Pass::Collect(collect) => collect,
+        Pass::Attach(..) => unreachable!(),
+    };
+    let anchors = &collect.anchors;
+    let scopes = &collect.scopes;
+
+    // Anchors grouped by the scope they are directly inside of, with the
+    // file's top level at index 0 and each braced scope at its index plus 1.
+    let mut by_start = vec![Vec::new(); scopes.len() + 1];
+    let mut by_end = vec![Vec::new(); scopes.len() + 1];
+    for (i, anchor) in anchors.iter().enumerate() {
+        let group = anchor.scope.map_or(0, |scope| scope + 1);
+        by_start[group].push(i);
+        by_end[group].push(i);
+    }
+    for group in &mut by_start {
+        group.sort_by_key(|&i| (anchors[i].start, i));
+    }
+    for group in &mut by_end {
+        group.sort_by_key(|&i| (anchors[i].end, i));
+    }
+
+    let mut order: Vec<usize> = (0..scopes.len()).collect();
+    order.sort_by_key(|&i| scopes[i].open);
+    let mut order = order.into_iter().peekable();
+    let mut open = Vec::new();
+
+    let mut pending = vec![Vec::new(); anchors.len()];
+    for comment in comments {
+        // Find the innermost scope containing the comment. Comments appear in
+        // source order, so a scope that ends ahead of one comment does not
+        // contain any later comment either.
+        while let Some(&next) = order.peek() {
+            if scopes[next].open > comment.start {
+                break;
+            }
+            open.push(next);
+            order.next();
+        }
+        while let Some(&innermost) = open.last() {
+            if comment.start < scopes[innermost].close {
+                break;
+            }
+            open.pop();
+        }
+        let scope = open.last().copied();
+
+        let group = scope.map_or(0, |scope| scope + 1);
+        let placement = placement(anchors, &by_start[group], &by_end[group], &comment);
+        for text in comment.lines() {
+            match placement {
+                Some((index, position)) => pending[index].push((position, text)),
+                None => match scope {
+                    Some(scope) => trailing
+                        .braces
+                        .entry(scopes[scope].close)
+                        .or_insert_with(Vec::new)
+                        .push(comment_attr(text)),
+                    None => trailing.file.push(comment_attr(text)),
+                },
+            }
+        }
+    }
+
+    let mut walk = Walk {
+        pass: Pass::Attach(pending, 0),
+    };
+    walk.visit_file_mut(file);
+    trailing
+}
+
+struct Comment {
+    text: String,
+    start: LineColumn,
+    end: LineColumn,
+}
+
+impl Comment {
+    // The printer turns a multi-line comment into a block comment only if it
+    // does not look like a doc comment, so split up the ones that would.
+    fn lines(self) -> Vec<String> {
+        if self.text.contains('\n') && self.text.starts_with('*') {
+            self.text.lines().map(str::to_owned).collect()
+        } else {
+            vec![self.text]
+        }
+    }
+}
+
+struct Anchor {
+    // Start of the node itself, not counting its outer attributes
+    start: LineColumn,
+    // Start of the node including its outer attributes
+    first: LineColumn,
+    end: LineColumn,
+    attrs: Vec<LineColumn>,
+    // Innermost braced scope the node is inside of, or None at the top level
+    // of the file
+    scope: Option<usize>,
+}
+
+// The inside of a pair of braces around a sequence of anchors, such as a
+// block, the fields of a struct or the items of an impl.
+struct Scope {
+    // Start of the opening brace
+    open: LineColumn,
+    // Start of the closing brace
+    close: LineColumn,
+}
+
+// Pick which node a comment belongs to out of the nodes in the comment's scope,
+// and where among that node's existing attributes it goes. A comment sharing a
+// line with a preceding single-line node is attached to that node, otherwise
+// to the next node that starts after it. Returns None for a comment at the end
+// of its scope.
+fn placement(
+    anchors: &[Anchor],
+    by_start: &[usize],
+    by_end: &[usize],
+    comment: &Comment,
+) -> Option<(usize, usize)> {
+    let before = by_end.partition_point(|&i| anchors[i].end <= comment.start);
+    if before > 0 {
+        let end = anchors[by_end[before - 1]].end;
+        let group = &by_end[by_end[..before].partition_point(|&i| anchors[i].end < end)..before];
+        let trailing = group
+            .iter()
+            .copied()
+            .find(|&i| end.line == comment.start.line && anchors[i].first.line == end.line);
+        if let Some(index) = trailing {
+            return Some((index, anchors[index].attrs.len()));
+        }
+    }
+
+    let after = by_start.partition_point(|&i| anchors[i].start < comment.end);
+    let index = *by_start.get(after)?;
+    let attrs = &anchors[index].attrs;
+    let position = attrs.partition_point(|&start| start < comment.start);
+    Some((index, position))
+}
+
+enum Pass {
+    Collect(Collect),
+    Attach(Vec<Vec<(usize, String)>>, usize),
+}
+
+#[derive(Default)]
+struct Collect {
+    anchors: Vec<Anchor>,
+    scopes: Vec<Scope>,
+    // Scopes that the walk is currently inside of, innermost last
+    stack: Vec<usize>,
+}
+
+struct Walk {
+    pass: Pass,
+}
+
+impl Walk {
+    fn scope(&mut self, brace: &token::Brace, f: impl FnOnce(&mut Self)) {
+        if let Pass::Collect(collect) = &mut self.pass {
+            collect.stack.push(collect.scopes.len());
+            collect.scopes.push(Scope {
+                open: brace.span.open().start(),
+                close: brace.span.close().start(),
+            });
+        }
+        f(self);
+        if let Pass::Collect(collect) = &mut self.pass {
+            collect.stack.pop();
+        }
+    }
+
+    fn anchor<T: Spanned>(
+        &mut self,
+        node: &mut T,
+        attrs: fn(&mut T) -> Option<&mut Vec<Attribute>>,
+    ) {
+        match &mut self.pass {
+            Pass::Collect(collect) => {
+                let taken = match attrs(node) {
+                    Some(attrs) => mem::take(attrs),
+                    None => return,
+                };
+                let span = node.span();
+                let first = taken
+                    .iter()
+                    .find(|attr| matches!(attr.style, AttrStyle::Outer))
+                    .map_or(span.start(), |attr| attr.pound_token.span.start());
+                collect.anchors.push(Anchor {
+                    start: span.start(),
+                    first,
+                    end: span.end(),
+                    attrs: taken
+                        .iter()
+                        .map(|attr| attr.pound_token.span.start())
+                        .collect(),
+                    scope: collect.stack.last().copied(),
+                });
+                *attrs(node).unwrap() = taken;
+            }
+            Pass::Attach(pending, next) => {
+                let attrs = match attrs(node) {
+                    Some(attrs) => attrs,
+                    None => return,
+                };
+                for (position, text) in mem::take(&mut pending[*next]).into_iter().rev() {
+                    attrs.insert(position, comment_attr(text));
+                }
+                *next += 1;
+            }
+        }
+    }
+}
+
+impl VisitMut for Walk {
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        self.anchor(item, item_attrs);
+        visit_mut::visit_item_mut(self, item);
+    }
+
+    fn visit_item_enum_mut(&mut self, item: &mut ItemEnum) {
+        let brace = item.brace_token;
+        self.scope(&brace, |walk| visit_mut::visit_item_enum_mut(walk, item));
+    }
+
+    fn visit_item_foreign_mod_mut(&mut self, item: &mut ItemForeignMod) {
+        let brace = item.brace_token;
+        self.scope(&brace, |walk| visit_mut::visit_item_foreign_mod_mut(walk, item));
+    }
+
+    fn visit_item_impl_mut(&mut self, item: &mut ItemImpl) {
+        let brace = item.brace_token;
+        self.scope(&brace, |walk| visit_mut::visit_item_impl_mut(walk, item));
+    }
+
+    fn visit_item_mod_mut(&mut self, item: &mut ItemMod) {
+        match &item.content {
+            Some((brace, _items)) => {
+                let brace = *brace;
+                self.scope(&brace, |walk| visit_mut::visit_item_mod_mut(walk, item));
+            }
+            None => visit_mut::visit_item_mod_mut(self, item),
+        }
+    }
+
+    fn visit_item_trait_mut(&mut self, item: &mut ItemTrait) {
+        let brace = item.brace_token;
+        self.scope(&brace, |walk| visit_mut::visit_item_trait_mut(walk, item));
+    }
+
+    fn visit_impl_item_mut(&mut self, item: &mut ImplItem) {
+        self.anchor(item, impl_item_attrs);
+        visit_mut::visit_impl_item_mut(self, item);
+    }
+
+    fn visit_trait_item_mut(&mut self, item: &mut TraitItem) {
+        self.anchor(item, trait_item_attrs);
+        visit_mut::visit_trait_item_mut(self, item);
+    }
+
+    fn visit_foreign_item_mut(&mut self, item: &mut ForeignItem) {
+        self.anchor(item, foreign_item_attrs);
+        visit_mut::visit_foreign_item_mut(self, item);
+    }
+
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Local(local) => self.anchor(local, |local: &mut Local| Some(&mut local.attrs)),
+            Stmt::Item(_) => {}
+            Stmt::Expr(expr, _) => self.anchor(expr, expr_attrs),
+            Stmt::Macro(mac) => self.anchor(mac, |mac: &mut StmtMacro| Some(&mut mac.attrs)),
+        }
+        visit_mut::visit_stmt_mut(self, stmt);
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        let brace = block.brace_token;
+        self.scope(&brace, |walk| visit_mut::visit_block_mut(walk, block));
+    }
+
+    fn visit_fields_named_mut(&mut self, fields: &mut FieldsNamed) {
+        let brace = fields.brace_token;
+        self.scope(&brace, |walk| visit_mut::visit_fields_named_mut(walk, fields));
+    }
+
+    fn visit_field_mut(&mut self, field: &mut Field) {
+        self.anchor(field, |field: &mut Field| Some(&mut field.attrs));
+        visit_mut::visit_field_mut(self, field);
+    }
+
+    fn visit_variant_mut(&mut self, variant: &mut Variant) {
+        self.anchor(variant, |variant: &mut Variant| Some(&mut variant.attrs));
+        visit_mut::visit_variant_mut(self, variant);
+    }
+
+    fn visit_expr_match_mut(&mut self, expr: &mut ExprMatch) {
+        let brace = expr.brace_token;
+        self.scope(&brace, |walk| visit_mut::visit_expr_match_mut(walk, expr));
+    }
+
+    fn visit_arm_mut(&mut self, arm: &mut Arm) {
+        self.anchor(arm, |arm: &mut Arm| Some(&mut arm.attrs));
+        visit_mut::visit_arm_mut(self, arm);
+    }
+}
+
+fn comment_attr(text: String) -> Attribute {
+    let span = Span::call_site();
+    Attribute {
+        pound_token: token::Pound::default(),
+        style: AttrStyle::Outer,
+        bracket_token: token::Bracket::default(),
+        meta: Meta::NameValue(MetaNameValue {
+            path: Path::from(Ident::new("comment", span)),
+            eq_token: token::Eq::default(),
+            value: Expr::Lit(ExprLit {
+                attrs: Vec::new(),
+                lit: Lit::Str(LitStr::new(&text, span)),
+            }),
+        }),
+    }
+}
+
+fn item_attrs(item: &mut Item) -> Option<&mut Vec<Attribute>> {
+    match item {
+        Item::Const(item) => Some(&mut item.attrs),
+        Item::Enum(item) => Some(&mut item.attrs),
+        Item::ExternCrate(item) => Some(&mut item.attrs),
+        Item::Fn(item) => Some(&mut item.attrs),
+        Item::ForeignMod(item) => Some(&mut item.attrs),
+        Item::Impl(item) => Some(&mut item.attrs),
+        Item::Macro(item) => Some(&mut item.attrs),
+        Item::Mod(item) => Some(&mut item.attrs),
+        Item::Static(item) => Some(&mut item.attrs),
+        Item::Struct(item) => Some(&mut item.attrs),
+        Item::Trait(item) => Some(&mut item.attrs),
+        Item::TraitAlias(item) => Some(&mut item.attrs),
+        Item::Type(item) => Some(&mut item.attrs),
+        Item::Union(item) => Some(&mut item.attrs),
+        Item::Use(item) => Some(&mut item.attrs),
+        Item::Verbatim(_) => None,
+        #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
+        _ => None,
+    }
+}
+
+fn impl_item_attrs(item: &mut ImplItem) -> Option<&mut Vec<Attribute>> {
+    match item {
+        ImplItem::Const(item) => Some(&mut item.attrs),
+        ImplItem::Fn(item) => Some(&mut item.attrs),
+        ImplItem::Type(item) => Some(&mut item.attrs),
+        ImplItem::Macro(item) => Some(&mut item.attrs),
+        ImplItem::Verbatim(_) => None,
+        #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
+        _ => None,
+    }
+}
+
+fn trait_item_attrs(item: &mut TraitItem) -> Option<&mut Vec<Attribute>> {
+    match item {
+        TraitItem::Const(item) => Some(&mut item.attrs),
+        TraitItem::Fn(item) => Some(&mut item.attrs),
+        TraitItem::Type(item) => Some(&mut item.attrs),
+        TraitItem::Macro(item) => Some(&mut item.attrs),
+        TraitItem::Verbatim(_) => None,
+        #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
+        _ => None,
+    }
+}
+
+fn foreign_item_attrs(item: &mut ForeignItem) -> Option<&mut Vec<Attribute>> {
+    match item {
+        ForeignItem::Fn(item) => Some(&mut item.attrs),
+        ForeignItem::Static(item) => Some(&mut item.attrs),
+        ForeignItem::Type(item) => Some(&mut item.attrs),
+        ForeignItem::Macro(item) => Some(&mut item.attrs),
+        ForeignItem::Verbatim(_) => None,
+        #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
+        _ => None,
+    }
+}
+
+fn expr_attrs(expr: &mut Expr) -> Option<&mut Vec<Attribute>> {
+    match expr {
+        Expr::Array(expr) => Some(&mut expr.attrs),
+        Expr::Assign(expr) => Some(&mut expr.attrs),
+        Expr::Async(expr) => Some(&mut expr.attrs),
+        Expr::Await(expr) => Some(&mut expr.attrs),
+        Expr::Binary(expr) => Some(&mut expr.attrs),
+        Expr::Block(expr) => Some(&mut expr.attrs),
+        Expr::Break(expr) => Some(&mut expr.attrs),
+        Expr::Call(expr) => Some(&mut expr.attrs),
+        Expr::Cast(expr) => Some(&mut expr.attrs),
+        Expr::Closure(expr) => Some(&mut expr.attrs),
+        Expr::Const(expr) => Some(&mut expr.attrs),
+        Expr::Continue(expr) => Some(&mut expr.attrs),
+        Expr::Field(expr) => Some(&mut expr.attrs),
+        Expr::ForLoop(expr) => Some(&mut expr.attrs),
+        Expr::Group(expr) => Some(&mut expr.attrs),
+        Expr::If(expr) => Some(&mut expr.attrs),
+        Expr::Index(expr) => Some(&mut expr.attrs),
+        Expr::Infer(expr) => Some(&mut expr.attrs),
+        Expr::Let(expr) => Some(&mut expr.attrs),
+        Expr::Lit(expr) => Some(&mut expr.attrs),
+        Expr::Loop(expr) => Some(&mut expr.attrs),
+        Expr::Macro(expr) => Some(&mut expr.attrs),
+        Expr::Match(expr) => Some(&mut expr.attrs),
+        Expr::MethodCall(expr) => Some(&mut expr.attrs),
+        Expr::Paren(expr) => Some(&mut expr.attrs),
+        Expr::Path(expr) => Some(&mut expr.attrs),
+        Expr::Range(expr) => Some(&mut expr.attrs),
+        Expr::Reference(expr) => Some(&mut expr.attrs),
+        Expr::Repeat(expr) => Some(&mut expr.attrs),
+        Expr::Return(expr) => Some(&mut expr.attrs),
+        Expr::Struct(expr) => Some(&mut expr.attrs),
+        Expr::Try(expr) => Some(&mut expr.attrs),
+        Expr::TryBlock(expr) => Some(&mut expr.attrs),
+        Expr::Tuple(expr) => Some(&mut expr.attrs),
+        Expr::Unary(expr) => Some(&mut expr.attrs),
+        Expr::Unsafe(expr) => Some(&mut expr.attrs),
+        Expr::Verbatim(_) => None,
+        Expr::While(expr) => Some(&mut expr.attrs),
+        Expr::Yield(expr) => Some(&mut expr.attrs),
+        #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
+        _ => None,
+    }
+}
+
+// Find the non-doc comments in a source file, skipping over string and
+// character literals. Positions follow proc_macro2::LineColumn: lines are
+// 1-based and columns count chars from 0.
+fn scan(source: &str) -> Vec<Comment> {
+    let mut source = source.strip_prefix('\u{feff}').unwrap_or(source);
+    let mut line = 1;
+    if source.starts_with("#!") && !source[2..].trim_start().starts_with('[') {
+        source = &source[source.find('\n').unwrap_or(source.len())..];
+    }
+
+    let chars: Vec<char> = source.chars().collect();
+    let mut comments = Vec::new();
+    let mut column = 0;
+    let mut i = 0;
+
+    macro_rules! bump {
+        () => {{
+            if chars[i] == '\n' {
+                line += 1;
+                column = 0;
+            } else {
+                column += 1;
+            }
+            i += 1;
+        }};
+    }
+
+    let peek = |i: usize| chars.get(i).copied().unwrap_or('\0');
+
+    while i < chars.len() {
+        let ch = chars[i];
+        if ch == '/' && peek(i + 1) == '/' {
+            let start = LineColumn { line, column };
+            let begin = i + 2;
+            while i < chars.len() && chars[i] != '\n' {
+                bump!();
+            }
+            let text: String = chars[begin..i].iter().collect();
+            let is_doc =
+                text.starts_with('!') || (text.starts_with('/') && !text.starts_with("//"));
+            if !is_doc {
+                let end = LineColumn { line, column };
+                let text = text.trim_end_matches('\r').to_owned();
+                comments.push(Comment { text, start, end });
+            }
+        } else if ch == '/' && peek(i + 1) == '*' {
+            let start = LineColumn { line, column };
+            let begin = i + 2;
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && peek(i + 1) == '*' {
+                    depth += 1;
+                    bump!();
+                } else if chars[i] == '*' && peek(i + 1) == '/' {
+                    depth -= 1;
+                    bump!();
+                    if depth == 0 {
+                        bump!();
+                        break;
+                    }
+                }
+                bump!();
+            }
+            let text: String = chars[begin..i.saturating_sub(2).max(begin)]
+                .iter()
+                .collect();
+            let is_doc = text.starts_with('!')
+                || (text.starts_with('*') && !text.starts_with("**") && text != "*");
+            if !is_doc {
+                let end = LineColumn { line, column };
+                comments.push(Comment { text, start, end });
+            }
+        } else if ch == '"' {
+            bump!();
+            while i < chars.len() && chars[i] != '"' {
+                if chars[i] == '\\' {
+                    bump!();
+                }
+                if i < chars.len() {
+                    bump!();
+                }
+            }
+            if i < chars.len() {
+                bump!();
+            }
+        } else if ch == '\'' {
+            bump!();
+            if peek(i) == '\\' {
+                while i < chars.len() && chars[i] != '\'' {
+                    if chars[i] == '\\' {
+                        bump!();
+                    }
+                    if i < chars.len() {
+                        bump!();
+                    }
+                }
+                if i < chars.len() {
+                    bump!();
+                }
+            } else if peek(i + 1) == '\'' {
+                bump!();
+                bump!();
+            }
+        } else if ch == '_' || ch.is_alphanumeric() {
+            let begin = i;
+            while i < chars.len() && (chars[i] == '_' || chars[i].is_alphanumeric()) {
+                bump!();
+            }
+            let prefix: String = chars[begin..i].iter().collect();
+            if let "r" | "br" | "cr" = prefix.as_str() {
+                let mut hashes = 0;
+                while peek(i + hashes) == '#' {
+                    hashes += 1;
+                }
+                if peek(i + hashes) == '"' {
+                    for _ in 0..=hashes {
+                        bump!();
+                    }
+                    while i < chars.len() {
+                        if chars[i] == '"' && (1..=hashes).all(|n| peek(i + n) == '#') {
+                            for _ in 0..=hashes {
+                                bump!();
+                            }
+                            break;
+                        }
+                        bump!();
+                    }
+                }
+            }
+        } else {
+            bump!();
+        }
+    }
+
+    comments
+}
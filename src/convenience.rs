@@ -1,11 +1,14 @@
 use crate::algorithm::{self, BeginToken, BreakToken, Breaks, Printer};
+use proc_macro2::Span;
 use std::borrow::Cow;
+use std::cmp;
 
-impl Printer {
+impl Printer<'_> {
     pub fn ibox(&mut self, indent: isize) {
         self.scan_begin(BeginToken {
             offset: indent,
             breaks: Breaks::Inconsistent,
+            max_width: algorithm::SIZE_INFINITY,
         });
     }
 
@@ -13,6 +16,17 @@ impl Printer {
         self.scan_begin(BeginToken {
             offset: indent,
             breaks: Breaks::Consistent,
+            max_width: algorithm::SIZE_INFINITY,
+        });
+    }
+
+    // Consistent box that also breaks if wider than max_width, without forcing
+    // the boxes around it to break.
+    pub fn cbox_with_max_width(&mut self, indent: isize, max_width: usize) {
+        self.scan_begin(BeginToken {
+            offset: indent,
+            breaks: Breaks::Consistent,
+            max_width: cmp::min(max_width, algorithm::SIZE_INFINITY as usize) as isize,
         });
     }
 
@@ -22,7 +36,12 @@ impl Printer {
 
     pub fn word<S: Into<Cow<'static, str>>>(&mut self, wrd: S) {
         let s = wrd.into();
-        self.scan_string(s);
+        self.scan_string(s, None);
+    }
+
+    pub fn word_spanned<S: Into<Cow<'static, str>>>(&mut self, wrd: S, span: Span) {
+        let s = wrd.into();
+        self.scan_string(s, Some(span));
     }
 
     fn spaces(&mut self, n: usize) {
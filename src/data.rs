@@ -1,10 +1,11 @@
 use crate::algorithm::Printer;
+use crate::fixup::FixupContext;
 use crate::iter::IterDelimited;
 use crate::path::PathKind;
 use crate::INDENT;
 use syn::{Field, Fields, FieldsUnnamed, Variant, VisRestricted, Visibility};
 
-impl Printer {
+impl Printer<'_> {
     pub fn variant(&mut self, variant: &Variant) {
         self.outer_attrs(&variant.attrs);
         self.ident(&variant.ident);
@@ -15,9 +16,11 @@ impl Printer {
                 self.cbox(INDENT);
                 self.space();
                 for field in fields.named.iter().delimited() {
+                    self.blank_lines_before(*field);
                     self.field(&field);
                     self.trailing_comma_or_space(field.is_last);
                 }
+                self.comments_before_close(&fields.brace_token);
                 self.offset(-INDENT);
                 self.end();
                 self.word("}");
@@ -31,7 +34,7 @@ impl Printer {
         }
         if let Some((_eq_token, discriminant)) = &variant.discriminant {
             self.word(" = ");
-            self.expr(discriminant);
+            self.expr(discriminant, FixupContext::NONE);
         }
     }
 
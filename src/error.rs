@@ -0,0 +1,95 @@
+use proc_macro2::TokenStream;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io;
+
+/// Error returned by [`try_unparse`][crate::try_unparse] when the syntax tree
+/// contains a node that prettyplease does not know how to print.
+#[derive(Clone, Debug)]
+pub struct UnparseError {
+    kind: &'static str,
+    tokens: Option<String>,
+}
+
+impl UnparseError {
+    pub(crate) fn unknown(kind: &'static str) -> Self {
+        UnparseError { kind, tokens: None }
+    }
+
+    pub(crate) fn verbatim(kind: &'static str, tokens: &TokenStream) -> Self {
+        UnparseError {
+            kind,
+            tokens: Some(tokens.to_string()),
+        }
+    }
+
+    /// The kind of syntax tree node that could not be printed, for example
+    /// `"Expr::Verbatim"` or `"Item"`.
+    pub fn kind(&self) -> &str {
+        self.kind
+    }
+
+    /// The tokens making up the offending node, if they are known.
+    pub fn tokens(&self) -> Option<&str> {
+        self.tokens.as_deref()
+    }
+}
+
+impl Display for UnparseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match &self.tokens {
+            Some(tokens) => write!(formatter, "{} `{}`", self.kind, tokens),
+            None => write!(formatter, "unknown {}", self.kind),
+        }
+    }
+}
+
+impl Error for UnparseError {}
+
+/// Error returned by [`unparse_to_writer`][crate::unparse_to_writer] and
+/// [`unparse_to_fmt_writer`][crate::unparse_to_fmt_writer].
+///
+/// Output is written as it is produced, so part of it may already have been
+/// written when the error occurs.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WriteError {
+    /// The syntax tree contains a node that prettyplease does not know how to
+    /// print.
+    Unsupported(UnparseError),
+    /// The [`io::Write`] failed.
+    Io(io::Error),
+    /// The [`fmt::Write`] failed.
+    Fmt(fmt::Error),
+}
+
+impl Display for WriteError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WriteError::Unsupported(error) => write!(formatter, "unable to format {}", error),
+            WriteError::Io(error) => Display::fmt(error, formatter),
+            WriteError::Fmt(error) => Display::fmt(error, formatter),
+        }
+    }
+}
+
+impl Error for WriteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WriteError::Unsupported(error) => Some(error),
+            WriteError::Io(error) => Some(error),
+            WriteError::Fmt(error) => Some(error),
+        }
+    }
+}
+
+impl From<WriteError> for io::Error {
+    fn from(error: WriteError) -> Self {
+        match error {
+            WriteError::Io(error) => error,
+            WriteError::Unsupported(_) | WriteError::Fmt(_) => {
+                io::Error::new(io::ErrorKind::Other, error)
+            }
+        }
+    }
+}
@@ -1,9 +1,12 @@
 use crate::algorithm::{BreakToken, Printer};
 use crate::attr;
+use crate::classify;
+use crate::fixup::FixupContext;
 use crate::iter::IterDelimited;
 use crate::path::PathKind;
+use crate::precedence::Precedence;
 use crate::stmt;
-use crate::INDENT;
+use crate::{MatchArmLeadingPipes, UnparseError, INDENT};
 use proc_macro2::TokenStream;
 use syn::punctuated::Punctuated;
 use syn::{
@@ -12,94 +15,144 @@ use syn::{
     ExprField, ExprForLoop, ExprGroup, ExprIf, ExprIndex, ExprInfer, ExprLet, ExprLit, ExprLoop,
     ExprMacro, ExprMatch, ExprMethodCall, ExprParen, ExprPath, ExprRange, ExprReference,
     ExprRepeat, ExprReturn, ExprStruct, ExprTry, ExprTryBlock, ExprTuple, ExprUnary, ExprUnsafe,
-    ExprWhile, ExprYield, FieldValue, Index, Label, Member, RangeLimits, ReturnType, Stmt, Token,
-    UnOp,
+    ExprWhile, ExprYield, FieldValue, Index, Label, Member, Pat, RangeLimits, ReturnType, Stmt,
+    Token, UnOp,
 };
 
-impl Printer {
-    pub fn expr(&mut self, expr: &Expr) {
+impl Printer<'_> {
+    pub fn expr(&mut self, expr: &Expr, mut fixup: FixupContext) {
+        let needs_paren = fixup.parenthesize(expr);
+        if needs_paren {
+            self.word("(");
+            fixup = FixupContext::NONE;
+        }
+
+        let beginning_of_line = false;
+
         match expr {
             Expr::Array(expr) => self.expr_array(expr),
-            Expr::Assign(expr) => self.expr_assign(expr),
+            Expr::Assign(expr) => self.expr_assign(expr, fixup),
             Expr::Async(expr) => self.expr_async(expr),
-            Expr::Await(expr) => self.expr_await(expr, false),
-            Expr::Binary(expr) => self.expr_binary(expr),
+            Expr::Await(expr) => self.expr_await(expr, beginning_of_line, fixup),
+            Expr::Binary(expr) => self.expr_binary(expr, fixup),
             Expr::Block(expr) => self.expr_block(expr),
-            Expr::Break(expr) => self.expr_break(expr),
-            Expr::Call(expr) => self.expr_call(expr, false),
-            Expr::Cast(expr) => self.expr_cast(expr),
-            Expr::Closure(expr) => self.expr_closure(expr),
+            Expr::Break(expr) => self.expr_break(expr, fixup),
+            Expr::Call(expr) => self.expr_call(expr, beginning_of_line, fixup),
+            Expr::Cast(expr) => self.expr_cast(expr, fixup),
+            Expr::Closure(expr) => self.expr_closure(expr, fixup),
             Expr::Const(expr) => self.expr_const(expr),
             Expr::Continue(expr) => self.expr_continue(expr),
-            Expr::Field(expr) => self.expr_field(expr, false),
+            Expr::Field(expr) => self.expr_field(expr, beginning_of_line, fixup),
             Expr::ForLoop(expr) => self.expr_for_loop(expr),
-            Expr::Group(expr) => self.expr_group(expr),
+            Expr::Group(expr) => self.expr_group(expr, fixup),
             Expr::If(expr) => self.expr_if(expr),
-            Expr::Index(expr) => self.expr_index(expr, false),
+            Expr::Index(expr) => self.expr_index(expr, beginning_of_line, fixup),
             Expr::Infer(expr) => self.expr_infer(expr),
-            Expr::Let(expr) => self.expr_let(expr),
+            Expr::Let(expr) => self.expr_let(expr, fixup),
             Expr::Lit(expr) => self.expr_lit(expr),
             Expr::Loop(expr) => self.expr_loop(expr),
             Expr::Macro(expr) => self.expr_macro(expr),
             Expr::Match(expr) => self.expr_match(expr),
-            Expr::MethodCall(expr) => self.expr_method_call(expr, false),
+            Expr::MethodCall(expr) => self.expr_method_call(expr, beginning_of_line, fixup),
             Expr::Paren(expr) => self.expr_paren(expr),
             Expr::Path(expr) => self.expr_path(expr),
-            Expr::Range(expr) => self.expr_range(expr),
-            Expr::Reference(expr) => self.expr_reference(expr),
+            Expr::Range(expr) => self.expr_range(expr, fixup),
+            Expr::Reference(expr) => self.expr_reference(expr, fixup),
             Expr::Repeat(expr) => self.expr_repeat(expr),
-            Expr::Return(expr) => self.expr_return(expr),
+            Expr::Return(expr) => self.expr_return(expr, fixup),
             Expr::Struct(expr) => self.expr_struct(expr),
-            Expr::Try(expr) => self.expr_try(expr, false),
+            Expr::Try(expr) => self.expr_try(expr, beginning_of_line, fixup),
             Expr::TryBlock(expr) => self.expr_try_block(expr),
             Expr::Tuple(expr) => self.expr_tuple(expr),
-            Expr::Unary(expr) => self.expr_unary(expr),
+            Expr::Unary(expr) => self.expr_unary(expr, fixup),
             Expr::Unsafe(expr) => self.expr_unsafe(expr),
-            Expr::Verbatim(expr) => self.expr_verbatim(expr),
+            Expr::Verbatim(expr) => self.expr_verbatim(expr, fixup),
             Expr::While(expr) => self.expr_while(expr),
-            Expr::Yield(expr) => self.expr_yield(expr),
+            Expr::Yield(expr) => self.expr_yield(expr, fixup),
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown Expr"),
+            _ => self.unsupported(UnparseError::unknown("Expr")),
+        }
+
+        if needs_paren {
+            self.word(")");
         }
     }
 
-    pub fn expr_beginning_of_line(&mut self, expr: &Expr, beginning_of_line: bool) {
+    pub fn expr_beginning_of_line(
+        &mut self,
+        expr: &Expr,
+        mut beginning_of_line: bool,
+        mut fixup: FixupContext,
+    ) {
+        let needs_paren = fixup.parenthesize(expr);
+        if needs_paren {
+            self.word("(");
+            beginning_of_line = false;
+            fixup = FixupContext::NONE;
+        }
+
         match expr {
-            Expr::Await(expr) => self.expr_await(expr, beginning_of_line),
-            Expr::Field(expr) => self.expr_field(expr, beginning_of_line),
-            Expr::Index(expr) => self.expr_index(expr, beginning_of_line),
-            Expr::MethodCall(expr) => self.expr_method_call(expr, beginning_of_line),
-            Expr::Try(expr) => self.expr_try(expr, beginning_of_line),
-            _ => self.expr(expr),
+            Expr::Await(expr) => self.expr_await(expr, beginning_of_line, fixup),
+            Expr::Field(expr) => self.expr_field(expr, beginning_of_line, fixup),
+            Expr::Index(expr) => self.expr_index(expr, beginning_of_line, fixup),
+            Expr::MethodCall(expr) => self.expr_method_call(expr, beginning_of_line, fixup),
+            Expr::Try(expr) => self.expr_try(expr, beginning_of_line, fixup),
+            _ => self.expr(expr, fixup),
+        }
+
+        if needs_paren {
+            self.word(")");
         }
     }
 
-    fn subexpr(&mut self, expr: &Expr, beginning_of_line: bool) {
+    fn prefix_subexpr(
+        &mut self,
+        expr: &Expr,
+        needs_paren: bool,
+        beginning_of_line: bool,
+        fixup: FixupContext,
+    ) {
+        if needs_paren {
+            self.cbox(-INDENT);
+            self.subexpr(expr, needs_paren, fixup);
+            self.end();
+            return;
+        }
+
         match expr {
-            Expr::Await(expr) => self.subexpr_await(expr, beginning_of_line),
-            Expr::Call(expr) => self.subexpr_call(expr),
-            Expr::Field(expr) => self.subexpr_field(expr, beginning_of_line),
-            Expr::Index(expr) => self.subexpr_index(expr, beginning_of_line),
-            Expr::MethodCall(expr) => self.subexpr_method_call(expr, beginning_of_line, false),
-            Expr::Try(expr) => self.subexpr_try(expr, beginning_of_line),
+            Expr::Await(expr) => self.prefix_subexpr_await(expr, beginning_of_line, fixup),
+            Expr::Call(expr) => self.prefix_subexpr_call(expr, fixup),
+            Expr::Field(expr) => self.prefix_subexpr_field(expr, beginning_of_line, fixup),
+            Expr::Index(expr) => self.prefix_subexpr_index(expr, beginning_of_line, fixup),
+            Expr::MethodCall(expr) => {
+                let unindent_call_args = false;
+                self.prefix_subexpr_method_call(expr, beginning_of_line, unindent_call_args, fixup);
+            }
+            Expr::Try(expr) => self.prefix_subexpr_try(expr, beginning_of_line, fixup),
             _ => {
                 self.cbox(-INDENT);
-                self.expr(expr);
+                self.expr(expr, fixup);
                 self.end();
             }
         }
     }
 
-    fn wrap_exterior_struct(&mut self, expr: &Expr) {
-        let needs_paren = contains_exterior_struct_lit(expr);
+    pub fn subexpr(&mut self, expr: &Expr, needs_paren: bool, mut fixup: FixupContext) {
         if needs_paren {
             self.word("(");
+            fixup = FixupContext::NONE;
         }
-        self.cbox(0);
-        self.expr(expr);
+
+        self.expr(expr, fixup);
+
         if needs_paren {
             self.word(")");
         }
+    }
+
+    fn expr_condition(&mut self, expr: &Expr) {
+        self.cbox(0);
+        self.expr(expr, FixupContext::new_condition());
         if needs_newline_if_wrap(expr) {
             self.space();
         } else {
@@ -111,10 +164,14 @@ impl Printer {
     fn expr_array(&mut self, expr: &ExprArray) {
         self.outer_attrs(&expr.attrs);
         self.word("[");
-        self.cbox(INDENT);
+        let options = self.options();
+        self.cbox_with_max_width(
+            INDENT,
+            options.heuristic_width(options.array_width, usize::MAX),
+        );
         self.zerobreak();
         for element in expr.elems.iter().delimited() {
-            self.expr(&element);
+            self.expr(&element, FixupContext::NONE);
             self.trailing_comma(element.is_last);
         }
         self.offset(-INDENT);
@@ -122,13 +179,28 @@ impl Printer {
         self.word("]");
     }
 
-    fn expr_assign(&mut self, expr: &ExprAssign) {
+    fn expr_assign(&mut self, expr: &ExprAssign, mut fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
+        let needs_paren = !attr::only_comments(&expr.attrs);
+        if needs_paren {
+            self.word("(");
+            fixup = FixupContext::NONE;
+        }
+        let (left_prec, left_fixup) = fixup.leftmost_subexpression_with_operator(
+            &expr.left,
+            false,
+            false,
+            Precedence::Assign,
+        );
+        let right_fixup = fixup.rightmost_subexpression_fixup(false, false, Precedence::Assign);
         self.ibox(0);
-        self.expr(&expr.left);
+        self.subexpr(&expr.left, left_prec <= Precedence::Range, left_fixup);
         self.word(" = ");
-        self.expr(&expr.right);
+        self.expr(&expr.right, right_fixup);
         self.end();
+        if needs_paren {
+            self.word(")");
+        }
     }
 
     fn expr_async(&mut self, expr: &ExprAsync) {
@@ -142,30 +214,78 @@ impl Printer {
         self.end();
     }
 
-    fn expr_await(&mut self, expr: &ExprAwait, beginning_of_line: bool) {
+    fn expr_await(&mut self, expr: &ExprAwait, beginning_of_line: bool, fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
-        self.cbox(INDENT);
-        self.subexpr_await(expr, beginning_of_line);
+        self.chain_box(method_calls_in_chain(&expr.base));
+        self.prefix_subexpr_await(expr, beginning_of_line, fixup);
         self.end();
     }
 
-    fn subexpr_await(&mut self, expr: &ExprAwait, beginning_of_line: bool) {
-        self.subexpr(&expr.base, beginning_of_line);
+    fn prefix_subexpr_await(
+        &mut self,
+        expr: &ExprAwait,
+        beginning_of_line: bool,
+        fixup: FixupContext,
+    ) {
+        let (left_prec, left_fixup) = fixup.leftmost_subexpression_with_dot(&expr.base);
+        self.prefix_subexpr(
+            &expr.base,
+            left_prec < Precedence::Unambiguous || classify::expr_absorbs_dot(&expr.base, false),
+            beginning_of_line,
+            left_fixup,
+        );
         self.zerobreak_unless_short_ident(beginning_of_line, &expr.base);
         self.word(".await");
     }
 
-    fn expr_binary(&mut self, expr: &ExprBinary) {
+    fn expr_binary(&mut self, expr: &ExprBinary, mut fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
+        let needs_paren = !attr::only_comments(&expr.attrs);
+        if needs_paren {
+            self.word("(");
+            fixup = FixupContext::NONE;
+        }
+        let binop_prec = Precedence::of_binop(&expr.op);
+        let (left_prec, left_fixup) = fixup.leftmost_subexpression_with_operator(
+            &expr.left,
+            match &expr.op {
+                BinOp::Sub(_)
+                | BinOp::Mul(_)
+                | BinOp::And(_)
+                | BinOp::Or(_)
+                | BinOp::BitAnd(_)
+                | BinOp::BitOr(_)
+                | BinOp::Shl(_)
+                | BinOp::Lt(_) => true,
+                _ => false,
+            },
+            match &expr.op {
+                BinOp::Shl(_) | BinOp::Lt(_) => true,
+                _ => false,
+            },
+            binop_prec,
+        );
+        let left_needs_paren = match binop_prec {
+            Precedence::Assign => left_prec <= Precedence::Range,
+            Precedence::Compare => left_prec <= binop_prec,
+            _ => left_prec < binop_prec,
+        };
+        let right_fixup = fixup.rightmost_subexpression_fixup(false, false, binop_prec);
+        let right_needs_paren = binop_prec != Precedence::Assign
+            && right_fixup.rightmost_subexpression_precedence(&expr.right) <= binop_prec;
+
         self.ibox(INDENT);
         self.ibox(-INDENT);
-        self.expr(&expr.left);
+        self.subexpr(&expr.left, left_needs_paren, left_fixup);
         self.end();
         self.space();
         self.binary_operator(&expr.op);
         self.nbsp();
-        self.expr(&expr.right);
+        self.subexpr(&expr.right, right_needs_paren, right_fixup);
         self.end();
+        if needs_paren {
+            self.word(")");
+        }
     }
 
     pub fn expr_block(&mut self, expr: &ExprBlock) {
@@ -178,7 +298,7 @@ impl Printer {
         self.end();
     }
 
-    fn expr_break(&mut self, expr: &ExprBreak) {
+    fn expr_break(&mut self, expr: &ExprBreak, fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
         self.word("break");
         if let Some(lifetime) = &expr.label {
@@ -187,38 +307,61 @@ impl Printer {
         }
         if let Some(value) = &expr.expr {
             self.nbsp();
-            self.expr(value);
+            self.subexpr(
+                value,
+                // Parenthesize `break 'inner: loop { break 'inner 1 } + 1`
+                //                     ^---------------------------------^
+                expr.label.is_none() && classify::expr_leading_label(value),
+                fixup.rightmost_subexpression_fixup(true, true, Precedence::Jump),
+            );
         }
     }
 
-    fn expr_call(&mut self, expr: &ExprCall, beginning_of_line: bool) {
+    fn expr_call(&mut self, expr: &ExprCall, beginning_of_line: bool, fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
-        self.expr_beginning_of_line(&expr.func, beginning_of_line);
+        let (func_paren, func_fixup) = call_func_needs_paren(expr, fixup);
+        if func_paren {
+            self.subexpr(&expr.func, func_paren, func_fixup);
+        } else {
+            self.expr_beginning_of_line(&expr.func, beginning_of_line, func_fixup);
+        }
         self.word("(");
         self.call_args(&expr.args);
         self.word(")");
     }
 
-    fn subexpr_call(&mut self, expr: &ExprCall) {
-        self.subexpr(&expr.func, false);
+    fn prefix_subexpr_call(&mut self, expr: &ExprCall, fixup: FixupContext) {
+        let (func_paren, func_fixup) = call_func_needs_paren(expr, fixup);
+        let beginning_of_line = false;
+        self.prefix_subexpr(&expr.func, func_paren, beginning_of_line, func_fixup);
         self.word("(");
         self.call_args(&expr.args);
         self.word(")");
     }
 
-    fn expr_cast(&mut self, expr: &ExprCast) {
+    fn expr_cast(&mut self, expr: &ExprCast, mut fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
+        let needs_paren = !attr::only_comments(&expr.attrs);
+        if needs_paren {
+            self.word("(");
+            fixup = FixupContext::NONE;
+        }
+        let (left_prec, left_fixup) =
+            fixup.leftmost_subexpression_with_operator(&expr.expr, false, false, Precedence::Cast);
         self.ibox(INDENT);
         self.ibox(-INDENT);
-        self.expr(&expr.expr);
+        self.subexpr(&expr.expr, left_prec < Precedence::Cast, left_fixup);
         self.end();
         self.space();
         self.word("as ");
         self.ty(&expr.ty);
         self.end();
+        if needs_paren {
+            self.word(")");
+        }
     }
 
-    fn expr_closure(&mut self, expr: &ExprClosure) {
+    fn expr_closure(&mut self, expr: &ExprClosure, fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
         self.ibox(0);
         if let Some(bound_lifetimes) = &expr.lifetimes {
@@ -255,7 +398,10 @@ impl Printer {
                 self.offset(-INDENT);
                 self.end();
                 self.neverbreak();
-                let wrap_in_brace = match &*expr.body {
+                let body = strip_paren_around_body(&expr.body);
+                let body_fixup =
+                    fixup.rightmost_subexpression_fixup(false, false, Precedence::Jump);
+                let wrap_in_brace = match body {
                     Expr::Match(ExprMatch { attrs, .. }) | Expr::Call(ExprCall { attrs, .. }) => {
                         attr::has_outer(attrs)
                     }
@@ -263,20 +409,21 @@ impl Printer {
                 };
                 if wrap_in_brace {
                     self.cbox(INDENT);
+                    let okay_to_brace = parseable_as_stmt(body);
                     self.scan_break(BreakToken {
-                        pre_break: Some('{'),
+                        pre_break: Some(if okay_to_brace { '{' } else { '(' }),
                         ..BreakToken::default()
                     });
-                    self.expr(&expr.body);
+                    self.expr_beginning_of_line(body, true, body_fixup);
                     self.scan_break(BreakToken {
                         offset: -INDENT,
-                        pre_break: stmt::add_semi(&expr.body).then(|| ';'),
-                        post_break: Some('}'),
+                        pre_break: (okay_to_brace && stmt::add_semi(body)).then(|| ';'),
+                        post_break: Some(if okay_to_brace { "}" } else { ")" }),
                         ..BreakToken::default()
                     });
                     self.end();
                 } else {
-                    self.expr(&expr.body);
+                    self.expr(body, body_fixup);
                 }
             }
             ReturnType::Type(_arrow, ty) => {
@@ -290,7 +437,25 @@ impl Printer {
                 self.ty(ty);
                 self.nbsp();
                 self.neverbreak();
-                self.expr(&expr.body);
+                match &*expr.body {
+                    Expr::Block(body) if body.attrs.is_empty() && body.label.is_none() => {
+                        self.expr_block(body);
+                    }
+                    // The body of a closure with an explicit return type must
+                    // be a block.
+                    body => {
+                        self.cbox(INDENT);
+                        self.word("{");
+                        self.space();
+                        self.ibox(0);
+                        self.expr(body, FixupContext::new_stmt());
+                        self.end();
+                        self.space();
+                        self.offset(-INDENT);
+                        self.word("}");
+                        self.end();
+                    }
+                }
             }
         }
         self.end();
@@ -313,15 +478,28 @@ impl Printer {
         }
     }
 
-    fn expr_field(&mut self, expr: &ExprField, beginning_of_line: bool) {
+    fn expr_field(&mut self, expr: &ExprField, beginning_of_line: bool, fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
-        self.cbox(INDENT);
-        self.subexpr_field(expr, beginning_of_line);
+        self.chain_box(method_calls_in_chain(&expr.base));
+        self.prefix_subexpr_field(expr, beginning_of_line, fixup);
         self.end();
     }
 
-    fn subexpr_field(&mut self, expr: &ExprField, beginning_of_line: bool) {
-        self.subexpr(&expr.base, beginning_of_line);
+    fn prefix_subexpr_field(
+        &mut self,
+        expr: &ExprField,
+        beginning_of_line: bool,
+        fixup: FixupContext,
+    ) {
+        let (left_prec, left_fixup) = fixup.leftmost_subexpression_with_dot(&expr.base);
+        let unnamed_member = matches!(expr.member, Member::Unnamed(_));
+        self.prefix_subexpr(
+            &expr.base,
+            left_prec < Precedence::Unambiguous
+                || classify::expr_absorbs_dot(&expr.base, unnamed_member),
+            beginning_of_line,
+            left_fixup,
+        );
         self.zerobreak_unless_short_ident(beginning_of_line, &expr.base);
         self.word(".");
         self.member(&expr.member);
@@ -337,24 +515,26 @@ impl Printer {
         self.pat(&expr.pat);
         self.word(" in ");
         self.neverbreak();
-        self.wrap_exterior_struct(&expr.expr);
+        self.expr_condition(&expr.expr);
         self.word("{");
         self.neverbreak();
         self.cbox(INDENT);
         self.hardbreak_if_nonempty();
         self.inner_attrs(&expr.attrs);
         for stmt in &expr.body.stmts {
+            self.blank_lines_before(stmt);
             self.stmt(stmt);
         }
+        self.comments_before_close(&expr.body.brace_token);
         self.offset(-INDENT);
         self.end();
         self.word("}");
         self.end();
     }
 
-    fn expr_group(&mut self, expr: &ExprGroup) {
+    fn expr_group(&mut self, expr: &ExprGroup, fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
-        self.expr(&expr.expr);
+        self.expr(&expr.expr, fixup);
     }
 
     fn expr_if(&mut self, expr: &ExprIf) {
@@ -362,7 +542,7 @@ impl Printer {
         self.cbox(INDENT);
         self.word("if ");
         self.cbox(-INDENT);
-        self.wrap_exterior_struct(&expr.cond);
+        self.expr_condition(&expr.cond);
         self.end();
         if let Some((_else_token, else_branch)) = &expr.else_branch {
             let mut else_branch = &**else_branch;
@@ -373,7 +553,7 @@ impl Printer {
                     Expr::If(expr) => {
                         self.word("if ");
                         self.cbox(-INDENT);
-                        self.wrap_exterior_struct(&expr.cond);
+                        self.expr_condition(&expr.cond);
                         self.end();
                         self.small_block(&expr.then_branch, &[]);
                         if let Some((_else_token, next)) = &expr.else_branch {
@@ -390,7 +570,7 @@ impl Printer {
                         self.word("{");
                         self.space();
                         self.ibox(INDENT);
-                        self.expr(other);
+                        self.expr(other, FixupContext::new_stmt());
                         self.end();
                         self.space();
                         self.offset(-INDENT);
@@ -399,32 +579,62 @@ impl Printer {
                 }
                 break;
             }
-        } else if expr.then_branch.stmts.is_empty() {
+        } else if expr.then_branch.stmts.is_empty()
+            && !self.has_comments_before_close(&expr.then_branch.brace_token)
+        {
             self.word("{}");
         } else {
             self.word("{");
             self.hardbreak();
             for stmt in &expr.then_branch.stmts {
+                self.blank_lines_before(stmt);
                 self.stmt(stmt);
             }
+            self.comments_before_close(&expr.then_branch.brace_token);
             self.offset(-INDENT);
             self.word("}");
         }
         self.end();
     }
 
-    fn expr_index(&mut self, expr: &ExprIndex, beginning_of_line: bool) {
+    fn expr_index(&mut self, expr: &ExprIndex, beginning_of_line: bool, fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
-        self.expr_beginning_of_line(&expr.expr, beginning_of_line);
+        let (left_prec, left_fixup) = fixup.leftmost_subexpression_with_operator(
+            &expr.expr,
+            true,
+            false,
+            Precedence::Unambiguous,
+        );
+        if left_prec < Precedence::Unambiguous {
+            self.subexpr(&expr.expr, true, left_fixup);
+        } else {
+            self.expr_beginning_of_line(&expr.expr, beginning_of_line, left_fixup);
+        }
         self.word("[");
-        self.expr(&expr.index);
+        self.expr(&expr.index, FixupContext::NONE);
         self.word("]");
     }
 
-    fn subexpr_index(&mut self, expr: &ExprIndex, beginning_of_line: bool) {
-        self.subexpr(&expr.expr, beginning_of_line);
+    fn prefix_subexpr_index(
+        &mut self,
+        expr: &ExprIndex,
+        beginning_of_line: bool,
+        fixup: FixupContext,
+    ) {
+        let (left_prec, left_fixup) = fixup.leftmost_subexpression_with_operator(
+            &expr.expr,
+            true,
+            false,
+            Precedence::Unambiguous,
+        );
+        self.prefix_subexpr(
+            &expr.expr,
+            left_prec < Precedence::Unambiguous,
+            beginning_of_line,
+            left_fixup,
+        );
         self.word("[");
-        self.expr(&expr.index);
+        self.expr(&expr.index, FixupContext::NONE);
         self.word("]");
     }
 
@@ -433,7 +643,7 @@ impl Printer {
         self.word("_");
     }
 
-    fn expr_let(&mut self, expr: &ExprLet) {
+    fn expr_let(&mut self, expr: &ExprLet, fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
         self.ibox(INDENT);
         self.word("let ");
@@ -442,14 +652,8 @@ impl Printer {
         self.end();
         self.space();
         self.word("= ");
-        let needs_paren = contains_exterior_struct_lit(&expr.expr);
-        if needs_paren {
-            self.word("(");
-        }
-        self.expr(&expr.expr);
-        if needs_paren {
-            self.word(")");
-        }
+        let (right_prec, right_fixup) = fixup.rightmost_subexpression(&expr.expr, Precedence::Let);
+        self.subexpr(&expr.expr, right_prec < Precedence::Let, right_fixup);
         self.end();
     }
 
@@ -468,8 +672,10 @@ impl Printer {
         self.hardbreak_if_nonempty();
         self.inner_attrs(&expr.attrs);
         for stmt in &expr.body.stmts {
+            self.blank_lines_before(stmt);
             self.stmt(stmt);
         }
+        self.comments_before_close(&expr.body.brace_token);
         self.offset(-INDENT);
         self.end();
         self.word("}");
@@ -484,7 +690,7 @@ impl Printer {
         self.outer_attrs(&expr.attrs);
         self.ibox(0);
         self.word("match ");
-        self.wrap_exterior_struct(&expr.expr);
+        self.expr_condition(&expr.expr);
         self.word("{");
         self.neverbreak();
         self.cbox(INDENT);
@@ -494,27 +700,41 @@ impl Printer {
             self.arm(arm);
             self.hardbreak();
         }
+        self.comments_before_close(&expr.brace_token);
         self.offset(-INDENT);
         self.end();
         self.word("}");
         self.end();
     }
 
-    fn expr_method_call(&mut self, expr: &ExprMethodCall, beginning_of_line: bool) {
+    fn expr_method_call(
+        &mut self,
+        expr: &ExprMethodCall,
+        beginning_of_line: bool,
+        fixup: FixupContext,
+    ) {
         self.outer_attrs(&expr.attrs);
-        self.cbox(INDENT);
+        self.chain_box(1 + method_calls_in_chain(&expr.receiver));
         let unindent_call_args = beginning_of_line && is_short_ident(&expr.receiver);
-        self.subexpr_method_call(expr, beginning_of_line, unindent_call_args);
+        self.prefix_subexpr_method_call(expr, beginning_of_line, unindent_call_args, fixup);
         self.end();
     }
 
-    fn subexpr_method_call(
+    fn prefix_subexpr_method_call(
         &mut self,
         expr: &ExprMethodCall,
         beginning_of_line: bool,
         unindent_call_args: bool,
+        fixup: FixupContext,
     ) {
-        self.subexpr(&expr.receiver, beginning_of_line);
+        let (left_prec, left_fixup) = fixup.leftmost_subexpression_with_dot(&expr.receiver);
+        self.prefix_subexpr(
+            &expr.receiver,
+            left_prec < Precedence::Unambiguous
+                || classify::expr_absorbs_dot(&expr.receiver, false),
+            beginning_of_line,
+            left_fixup,
+        );
         self.zerobreak_unless_short_ident(beginning_of_line, &expr.receiver);
         self.word(".");
         self.ident(&expr.method);
@@ -531,7 +751,7 @@ impl Printer {
     fn expr_paren(&mut self, expr: &ExprParen) {
         self.outer_attrs(&expr.attrs);
         self.word("(");
-        self.expr(&expr.expr);
+        self.expr(&expr.expr, FixupContext::NONE);
         self.word(")");
     }
 
@@ -540,44 +760,68 @@ impl Printer {
         self.qpath(&expr.qself, &expr.path, PathKind::Expr);
     }
 
-    pub fn expr_range(&mut self, expr: &ExprRange) {
+    pub fn expr_range(&mut self, expr: &ExprRange, mut fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
+        let needs_paren = !attr::only_comments(&expr.attrs);
+        if needs_paren {
+            self.word("(");
+            fixup = FixupContext::NONE;
+        }
         if let Some(start) = &expr.start {
-            self.expr(start);
+            let (left_prec, left_fixup) =
+                fixup.leftmost_subexpression_with_operator(start, true, false, Precedence::Range);
+            self.subexpr(start, left_prec <= Precedence::Range, left_fixup);
         }
         self.word(match expr.limits {
             RangeLimits::HalfOpen(_) => "..",
             RangeLimits::Closed(_) => "..=",
         });
         if let Some(end) = &expr.end {
-            self.expr(end);
+            let right_fixup = fixup.rightmost_subexpression_fixup(false, true, Precedence::Range);
+            let right_prec = right_fixup.rightmost_subexpression_precedence(end);
+            let right_needs_paren = right_prec <= Precedence::Range;
+            if !right_needs_paren {
+                if let Expr::Range(ExprRange { start: None, .. }) = &**end {
+                    // `....=x` would not lex as `..` followed by `..=x`.
+                    self.nbsp();
+                }
+            }
+            self.subexpr(end, right_needs_paren, right_fixup);
+        }
+        if needs_paren {
+            self.word(")");
         }
     }
 
-    fn expr_reference(&mut self, expr: &ExprReference) {
+    fn expr_reference(&mut self, expr: &ExprReference, fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
         self.word("&");
         if expr.mutability.is_some() {
             self.word("mut ");
         }
-        self.expr(&expr.expr);
+        let (right_prec, right_fixup) =
+            fixup.rightmost_subexpression(&expr.expr, Precedence::Prefix);
+        self.subexpr(&expr.expr, right_prec < Precedence::Prefix, right_fixup);
     }
 
     fn expr_repeat(&mut self, expr: &ExprRepeat) {
         self.outer_attrs(&expr.attrs);
         self.word("[");
-        self.expr(&expr.expr);
+        self.expr(&expr.expr, FixupContext::NONE);
         self.word("; ");
-        self.expr(&expr.len);
+        self.expr(&expr.len, FixupContext::NONE);
         self.word("]");
     }
 
-    fn expr_return(&mut self, expr: &ExprReturn) {
+    fn expr_return(&mut self, expr: &ExprReturn, fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
         self.word("return");
         if let Some(value) = &expr.expr {
             self.nbsp();
-            self.expr(value);
+            self.expr(
+                value,
+                fixup.rightmost_subexpression_fixup(true, false, Precedence::Jump),
+            );
         }
     }
 
@@ -595,22 +839,43 @@ impl Printer {
         }
         if let Some(rest) = &expr.rest {
             self.word("..");
-            self.expr(rest);
+            self.expr(rest, FixupContext::NONE);
             self.space();
         }
         self.offset(-INDENT);
-        self.end_with_max_width(34);
+        if expr.fields.is_empty() && expr.rest.is_none() {
+            self.end();
+        } else {
+            let options = self.options();
+            let width = options.heuristic_width(options.struct_lit_width, 0);
+            if width < options.max_width {
+                self.end_with_max_width(width as isize);
+            } else {
+                self.end();
+            }
+        }
         self.word("}");
     }
 
-    fn expr_try(&mut self, expr: &ExprTry, beginning_of_line: bool) {
+    fn expr_try(&mut self, expr: &ExprTry, beginning_of_line: bool, fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
-        self.expr_beginning_of_line(&expr.expr, beginning_of_line);
+        let (left_prec, left_fixup) = fixup.leftmost_subexpression_with_dot(&expr.expr);
+        if left_prec < Precedence::Unambiguous {
+            self.subexpr(&expr.expr, true, left_fixup);
+        } else {
+            self.expr_beginning_of_line(&expr.expr, beginning_of_line, left_fixup);
+        }
         self.word("?");
     }
 
-    fn subexpr_try(&mut self, expr: &ExprTry, beginning_of_line: bool) {
-        self.subexpr(&expr.expr, beginning_of_line);
+    fn prefix_subexpr_try(&mut self, expr: &ExprTry, beginning_of_line: bool, fixup: FixupContext) {
+        let (left_prec, left_fixup) = fixup.leftmost_subexpression_with_dot(&expr.expr);
+        self.prefix_subexpr(
+            &expr.expr,
+            left_prec < Precedence::Unambiguous,
+            beginning_of_line,
+            left_fixup,
+        );
         self.word("?");
     }
 
@@ -628,7 +893,7 @@ impl Printer {
         self.cbox(INDENT);
         self.zerobreak();
         for elem in expr.elems.iter().delimited() {
-            self.expr(&elem);
+            self.expr(&elem, FixupContext::NONE);
             if expr.elems.len() == 1 {
                 self.word(",");
                 self.zerobreak();
@@ -641,10 +906,12 @@ impl Printer {
         self.word(")");
     }
 
-    fn expr_unary(&mut self, expr: &ExprUnary) {
+    fn expr_unary(&mut self, expr: &ExprUnary, fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
         self.unary_operator(&expr.op);
-        self.expr(&expr.expr);
+        let (right_prec, right_fixup) =
+            fixup.rightmost_subexpression(&expr.expr, Precedence::Prefix);
+        self.subexpr(&expr.expr, right_prec < Precedence::Prefix, right_fixup);
     }
 
     fn expr_unsafe(&mut self, expr: &ExprUnsafe) {
@@ -656,14 +923,14 @@ impl Printer {
     }
 
     #[cfg(not(feature = "verbatim"))]
-    fn expr_verbatim(&mut self, expr: &TokenStream) {
+    fn expr_verbatim(&mut self, expr: &TokenStream, _fixup: FixupContext) {
         if !expr.is_empty() {
-            unimplemented!("Expr::Verbatim `{}`", expr);
+            self.unsupported_verbatim("Expr::Verbatim", expr);
         }
     }
 
     #[cfg(feature = "verbatim")]
-    fn expr_verbatim(&mut self, tokens: &TokenStream) {
+    fn expr_verbatim(&mut self, tokens: &TokenStream, fixup: FixupContext) {
         use syn::parse::{Parse, ParseStream, Result};
 
         enum ExprVerbatim {
@@ -702,7 +969,7 @@ impl Printer {
 
         let expr: ExprVerbatim = match syn::parse2(tokens.clone()) {
             Ok(expr) => expr,
-            Err(_) => unimplemented!("Expr::Verbatim `{}`", tokens),
+            Err(_) => return self.unsupported_verbatim("Expr::Verbatim", tokens),
         };
 
         match expr {
@@ -710,7 +977,9 @@ impl Printer {
             ExprVerbatim::RawReference(expr) => {
                 self.word("&raw ");
                 self.word(if expr.mutable { "mut " } else { "const " });
-                self.expr(&expr.expr);
+                let (right_prec, right_fixup) =
+                    fixup.rightmost_subexpression(&expr.expr, Precedence::Prefix);
+                self.subexpr(&expr.expr, right_prec < Precedence::Prefix, right_fixup);
             }
         }
     }
@@ -721,26 +990,31 @@ impl Printer {
             self.label(label);
         }
         self.word("while ");
-        self.wrap_exterior_struct(&expr.cond);
+        self.expr_condition(&expr.cond);
         self.word("{");
         self.neverbreak();
         self.cbox(INDENT);
         self.hardbreak_if_nonempty();
         self.inner_attrs(&expr.attrs);
         for stmt in &expr.body.stmts {
+            self.blank_lines_before(stmt);
             self.stmt(stmt);
         }
+        self.comments_before_close(&expr.body.brace_token);
         self.offset(-INDENT);
         self.end();
         self.word("}");
     }
 
-    fn expr_yield(&mut self, expr: &ExprYield) {
+    fn expr_yield(&mut self, expr: &ExprYield, fixup: FixupContext) {
         self.outer_attrs(&expr.attrs);
         self.word("yield");
         if let Some(value) = &expr.expr {
             self.nbsp();
-            self.expr(value);
+            self.expr(
+                value,
+                fixup.rightmost_subexpression_fixup(true, false, Precedence::Jump),
+            );
         }
     }
 
@@ -755,7 +1029,7 @@ impl Printer {
         if field_value.colon_token.is_some() {
             self.word(": ");
             self.ibox(0);
-            self.expr(&field_value.expr);
+            self.expr(&field_value.expr, FixupContext::NONE);
             self.end();
         }
     }
@@ -763,16 +1037,26 @@ impl Printer {
     fn arm(&mut self, arm: &Arm) {
         self.outer_attrs(&arm.attrs);
         self.ibox(0);
-        self.pat(&arm.pat);
+        let leading_vert = match self.options().match_arm_leading_pipes {
+            MatchArmLeadingPipes::Never => false,
+            MatchArmLeadingPipes::Always => true,
+            MatchArmLeadingPipes::Preserve => {
+                matches!(&arm.pat, Pat::Or(pat) if pat.leading_vert.is_some())
+            }
+        };
+        self.arm_pat(&arm.pat, leading_vert);
         if let Some((_if_token, guard)) = &arm.guard {
             self.word(" if ");
-            self.expr(guard);
+            self.expr(guard, FixupContext::NONE);
         }
         self.word(" =>");
         let empty_block;
         let mut body = &*arm.body;
         while let Expr::Block(expr) = body {
-            if expr.attrs.is_empty() && expr.label.is_none() {
+            if expr.attrs.is_empty()
+                && expr.label.is_none()
+                && !self.has_comments_before_close(&expr.block.brace_token)
+            {
                 let mut stmts = expr.block.stmts.iter();
                 if let (Some(Stmt::Expr(inner, None)), None) = (stmts.next(), stmts.next()) {
                     body = inner;
@@ -805,13 +1089,18 @@ impl Printer {
             self.hardbreak_if_nonempty();
             self.inner_attrs(&body.attrs);
             for stmt in &body.block.stmts {
+                self.blank_lines_before(stmt);
                 self.stmt(stmt);
             }
+            self.comments_before_close(&body.block.brace_token);
             self.offset(-INDENT);
             self.end();
             self.word("}");
+            if self.options().match_block_trailing_comma {
+                self.word(",");
+            }
             self.end();
-        } else {
+        } else if self.options().match_arm_blocks {
             self.nbsp();
             self.neverbreak();
             self.cbox(INDENT);
@@ -819,16 +1108,36 @@ impl Printer {
                 pre_break: Some('{'),
                 ..BreakToken::default()
             });
-            self.expr_beginning_of_line(body, true);
+            self.expr_beginning_of_line(body, true, FixupContext::new_match_arm());
             self.scan_break(BreakToken {
                 offset: -INDENT,
                 pre_break: stmt::add_semi(body).then(|| ';'),
-                post_break: Some('}'),
+                post_break: Some(if self.options().match_block_trailing_comma {
+                    "},"
+                } else {
+                    "}"
+                }),
                 no_break: requires_terminator(body).then(|| ','),
                 ..BreakToken::default()
             });
             self.end();
             self.end();
+        } else if is_blocklike(body) || !requires_terminator(body) {
+            self.nbsp();
+            self.neverbreak();
+            self.expr_beginning_of_line(body, true, FixupContext::new_match_arm());
+            if requires_terminator(body) || self.options().match_block_trailing_comma {
+                self.word(",");
+            }
+            self.end();
+        } else {
+            self.neverbreak();
+            self.cbox(INDENT);
+            self.space();
+            self.expr_beginning_of_line(body, true, FixupContext::new_match_arm());
+            self.word(",");
+            self.end();
+            self.end();
         }
     }
 
@@ -836,13 +1145,17 @@ impl Printer {
         let mut iter = args.iter();
         match (iter.next(), iter.next()) {
             (Some(expr), None) if is_blocklike(expr) => {
-                self.expr(expr);
+                self.expr(expr, FixupContext::NONE);
             }
             _ => {
-                self.cbox(INDENT);
+                let options = self.options();
+                self.cbox_with_max_width(
+                    INDENT,
+                    options.heuristic_width(options.fn_call_width, usize::MAX),
+                );
                 self.zerobreak();
                 for arg in args.iter().delimited() {
-                    self.expr(&arg);
+                    self.expr(&arg, FixupContext::NONE);
                     self.trailing_comma(arg.is_last);
                 }
                 self.offset(-INDENT);
@@ -853,22 +1166,27 @@ impl Printer {
 
     pub fn small_block(&mut self, block: &Block, attrs: &[Attribute]) {
         self.word("{");
-        if attr::has_inner(attrs) || !block.stmts.is_empty() {
+        if attr::has_inner(attrs)
+            || !block.stmts.is_empty()
+            || self.has_comments_before_close(&block.brace_token)
+        {
             self.space();
             self.inner_attrs(attrs);
             match (block.stmts.get(0), block.stmts.get(1)) {
                 (Some(Stmt::Expr(expr, None)), None) if stmt::break_after(expr) => {
                     self.ibox(0);
-                    self.expr_beginning_of_line(expr, true);
+                    self.expr_beginning_of_line(expr, true, FixupContext::new_stmt());
                     self.end();
                     self.space();
                 }
                 _ => {
                     for stmt in &block.stmts {
+                        self.blank_lines_before(stmt);
                         self.stmt(stmt);
                     }
                 }
             }
+            self.comments_before_close(&block.brace_token);
             self.offset(-INDENT);
         }
         self.word("}");
@@ -882,11 +1200,11 @@ impl Printer {
     }
 
     fn index(&mut self, member: &Index) {
-        self.word(member.index.to_string());
+        self.word_spanned(member.index.to_string(), member.span);
     }
 
     fn binary_operator(&mut self, op: &BinOp) {
-        self.word(match op {
+        let op = match op {
             BinOp::Add(_) => "+",
             BinOp::Sub(_) => "-",
             BinOp::Mul(_) => "*",
@@ -916,18 +1234,32 @@ impl Printer {
             BinOp::ShlAssign(_) => "<<=",
             BinOp::ShrAssign(_) => ">>=",
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown BinOp"),
-        });
+            _ => return self.unsupported(UnparseError::unknown("BinOp")),
+        };
+        self.word(op);
     }
 
     fn unary_operator(&mut self, op: &UnOp) {
-        self.word(match op {
+        let op = match op {
             UnOp::Deref(_) => "*",
             UnOp::Not(_) => "!",
             UnOp::Neg(_) => "-",
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown UnOp"),
-        });
+            _ => return self.unsupported(UnparseError::unknown("UnOp")),
+        };
+        self.word(op);
+    }
+
+    // Box around a chain of method calls, field accesses and awaits, in which
+    // either every link goes on its own line or none do.
+    fn chain_box(&mut self, method_calls: usize) {
+        let options = self.options();
+        let max_width = if options.chain_one_call_per_line && method_calls >= 2 {
+            0
+        } else {
+            options.heuristic_width(options.chain_width, usize::MAX)
+        };
+        self.cbox_with_max_width(INDENT, max_width);
     }
 
     fn zerobreak_unless_short_ident(&mut self, beginning_of_line: bool, expr: &Expr) {
@@ -986,41 +1318,52 @@ pub fn requires_terminator(expr: &Expr) -> bool {
     }
 }
 
-// Expressions that syntactically contain an "exterior" struct literal i.e. not
-// surrounded by any parens or other delimiters. For example `X { y: 1 }`, `X {
-// y: 1 }.method()`, `foo == X { y: 1 }` and `X { y: 1 } == foo` all do, but `(X
-// { y: 1 }) == foo` does not.
-fn contains_exterior_struct_lit(expr: &Expr) -> bool {
-    match expr {
-        Expr::Struct(_) => true,
+fn call_func_needs_paren(expr: &ExprCall, fixup: FixupContext) -> (bool, FixupContext) {
+    let (func_prec, func_fixup) = fixup.leftmost_subexpression_with_operator(
+        &expr.func,
+        true,
+        false,
+        Precedence::Unambiguous,
+    );
+    let needs_paren = if let Expr::Field(func) = &*expr.func {
+        // `(s.f)()` calls the value of field `f`, while `s.f()` would be a
+        // method call.
+        matches!(func.member, Member::Named(_))
+    } else {
+        func_prec < Precedence::Unambiguous
+    };
+    (needs_paren, func_fixup)
+}
 
-        Expr::Assign(ExprAssign { left, right, .. })
-        | Expr::Binary(ExprBinary { left, right, .. }) => {
-            // X { y: 1 } + X { y: 2 }
-            contains_exterior_struct_lit(left) || contains_exterior_struct_lit(right)
+// Whether the expression can be printed as the first statement of a block
+// without changing its meaning. A block-like expression at the start of a
+// statement ends the statement, so `match x {}[0]` would instead parse as a
+// match followed by an array.
+fn parseable_as_stmt(expr: &Expr) -> bool {
+    match expr {
+        Expr::Assign(ExprAssign { left: e, .. })
+        | Expr::Binary(ExprBinary { left: e, .. })
+        | Expr::Call(ExprCall { func: e, .. })
+        | Expr::Cast(ExprCast { expr: e, .. })
+        | Expr::Index(ExprIndex { expr: e, .. })
+        | Expr::Range(ExprRange { start: Some(e), .. }) => {
+            requires_terminator(e) && parseable_as_stmt(e)
         }
 
         Expr::Await(ExprAwait { base: e, .. })
-        | Expr::Cast(ExprCast { expr: e, .. })
         | Expr::Field(ExprField { base: e, .. })
-        | Expr::Index(ExprIndex { expr: e, .. })
+        | Expr::Group(ExprGroup { expr: e, .. })
         | Expr::MethodCall(ExprMethodCall { receiver: e, .. })
-        | Expr::Reference(ExprReference { expr: e, .. })
-        | Expr::Unary(ExprUnary { expr: e, .. }) => {
-            // &X { y: 1 }, X { y: 1 }.y
-            contains_exterior_struct_lit(e)
-        }
+        | Expr::Try(ExprTry { expr: e, .. }) => parseable_as_stmt(e),
 
         Expr::Array(_)
         | Expr::Async(_)
         | Expr::Block(_)
         | Expr::Break(_)
-        | Expr::Call(_)
         | Expr::Closure(_)
         | Expr::Const(_)
         | Expr::Continue(_)
         | Expr::ForLoop(_)
-        | Expr::Group(_)
         | Expr::If(_)
         | Expr::Infer(_)
         | Expr::Let(_)
@@ -1030,20 +1373,33 @@ fn contains_exterior_struct_lit(expr: &Expr) -> bool {
         | Expr::Match(_)
         | Expr::Paren(_)
         | Expr::Path(_)
-        | Expr::Range(_)
+        | Expr::Range(ExprRange { start: None, .. })
+        | Expr::Reference(_)
         | Expr::Repeat(_)
         | Expr::Return(_)
-        | Expr::Try(_)
+        | Expr::Struct(_)
         | Expr::TryBlock(_)
         | Expr::Tuple(_)
+        | Expr::Unary(_)
         | Expr::Unsafe(_)
         | Expr::Verbatim(_)
         | Expr::While(_)
-        | Expr::Yield(_) => false,
+        | Expr::Yield(_) => true,
 
         #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-        _ => false,
+        _ => true,
+    }
+}
+
+// Parentheses around a closure body that the printer would put back anyway if
+// the body needs to be wrapped onto its own lines.
+fn strip_paren_around_body(body: &Expr) -> &Expr {
+    if let Expr::Paren(paren) = body {
+        if paren.attrs.is_empty() && !parseable_as_stmt(&paren.expr) {
+            return &paren.expr;
+        }
     }
+    body
 }
 
 fn needs_newline_if_wrap(expr: &Expr) -> bool {
@@ -1111,6 +1467,22 @@ fn is_short_ident(expr: &Expr) -> bool {
     false
 }
 
+// Number of method calls in the chain that `expr` is the receiver of.
+fn method_calls_in_chain(mut expr: &Expr) -> usize {
+    let mut count = 0;
+    loop {
+        expr = match expr {
+            Expr::MethodCall(expr) => {
+                count += 1;
+                &expr.receiver
+            }
+            Expr::Await(ExprAwait { base, .. }) | Expr::Field(ExprField { base, .. }) => base,
+            Expr::Index(ExprIndex { expr, .. }) | Expr::Try(ExprTry { expr, .. }) => expr,
+            _ => return count,
+        };
+    }
+}
+
 fn is_blocklike(expr: &Expr) -> bool {
     match expr {
         Expr::Array(ExprArray { attrs, .. })
@@ -1,17 +1,26 @@
 use crate::algorithm::Printer;
+#[cfg(feature = "span-locations")]
+use crate::blank::BlankLines;
+#[cfg(feature = "unparen")]
+use crate::unparen;
 use syn::File;
 
-impl Printer {
+impl Printer<'_> {
     pub fn file(&mut self, file: &File) {
+        #[cfg(feature = "unparen")]
+        let file = &unparen::file(file, self.options());
+        #[cfg(feature = "span-locations")]
+        if self.options().blank_lines > 0 {
+            let max = self.options().blank_lines;
+            self.set_blank_lines(BlankLines::new(file, self.source(), max));
+        }
         self.cbox(0);
         if let Some(shebang) = &file.shebang {
             self.word(shebang.clone());
             self.hardbreak();
         }
         self.inner_attrs(&file.attrs);
-        for item in &file.items {
-            self.item(item);
-        }
+        self.items(&file.items);
         self.end();
     }
 }
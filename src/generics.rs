@@ -1,7 +1,8 @@
 use crate::algorithm::Printer;
+use crate::fixup::FixupContext;
 use crate::iter::IterDelimited;
 use crate::path::PathKind;
-use crate::INDENT;
+use crate::{BraceStyle, UnparseError, INDENT};
 use proc_macro2::TokenStream;
 use std::ptr;
 use syn::{
@@ -10,7 +11,7 @@ use syn::{
     WherePredicate,
 };
 
-impl Printer {
+impl Printer<'_> {
     pub fn generics(&mut self, generics: &Generics) {
         if generics.params.is_empty() {
             return;
@@ -110,7 +111,7 @@ impl Printer {
             TypeParamBound::Lifetime(lifetime) => self.lifetime(lifetime),
             TypeParamBound::Verbatim(bound) => self.type_param_bound_verbatim(bound),
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown TypeParamBound"),
+            _ => self.unsupported(UnparseError::unknown("TypeParamBound")),
         }
     }
 
@@ -145,7 +146,7 @@ impl Printer {
 
     #[cfg(not(feature = "verbatim"))]
     fn type_param_bound_verbatim(&mut self, bound: &TokenStream) {
-        unimplemented!("TypeParamBound::Verbatim `{}`", bound);
+        self.unsupported_verbatim("TypeParamBound::Verbatim", bound);
     }
 
     #[cfg(feature = "verbatim")]
@@ -175,7 +176,7 @@ impl Printer {
 
         let bound: TypeParamBoundVerbatim = match syn::parse2(tokens.clone()) {
             Ok(bound) => bound,
-            Err(_) => unimplemented!("TypeParamBound::Verbatim `{}`", tokens),
+            Err(_) => return self.unsupported_verbatim("TypeParamBound::Verbatim", tokens),
         };
 
         match bound {
@@ -194,11 +195,22 @@ impl Printer {
         self.ty(&const_param.ty);
         if let Some(default) = &const_param.default {
             self.word(" = ");
-            self.expr(default);
+            self.expr(default, FixupContext::NONE);
         }
     }
 
-    pub fn where_clause_for_body(&mut self, where_clause: &Option<WhereClause>) {
+    pub fn where_clause_for_body(&mut self, where_clause: &Option<WhereClause>, empty_body: bool) {
+        let has_predicates = where_clause
+            .as_ref()
+            .map_or(false, |where_clause| !where_clause.predicates.is_empty());
+        if !has_predicates
+            && !empty_body
+            && self.options().brace_style == BraceStyle::AlwaysNextLine
+        {
+            self.hardbreak();
+            self.offset(-INDENT);
+            return;
+        }
         let hardbreaks = true;
         let semi = false;
         self.where_clause_impl(where_clause, hardbreaks, semi);
@@ -239,7 +251,18 @@ impl Printer {
                 return;
             }
         };
-        if hardbreaks {
+        if hardbreaks && self.options().where_single_line && where_clause.predicates.len() == 1 {
+            self.hardbreak();
+            self.offset(-INDENT);
+            self.word("where ");
+            self.where_predicate(&where_clause.predicates[0]);
+            if semi {
+                self.word(";");
+            } else {
+                self.hardbreak();
+                self.offset(-INDENT);
+            }
+        } else if hardbreaks {
             self.hardbreak();
             self.offset(-INDENT);
             self.word("where");
@@ -280,7 +303,7 @@ impl Printer {
             WherePredicate::Type(predicate) => self.predicate_type(predicate),
             WherePredicate::Lifetime(predicate) => self.predicate_lifetime(predicate),
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown WherePredicate"),
+            _ => self.unsupported(UnparseError::unknown("WherePredicate")),
         }
     }
 
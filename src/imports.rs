@@ -0,0 +1,611 @@
+use crate::algorithm::Printer;
+use crate::options::ImportsGranularity;
+use proc_macro2::Ident;
+use std::cmp::Ordering;
+use std::mem;
+use std::ptr;
+use syn::punctuated::Punctuated;
+use syn::{
+    token, Item, ItemUse, Path, UseGlob, UseGroup, UseName, UsePath, UseRename, UseTree, Visibility,
+};
+
+impl Printer<'_> {
+    pub fn items(&mut self, items: &[Item]) {
+        let rearrange = self.options().reorder_imports
+            || self.options().imports_granularity != ImportsGranularity::Preserve;
+        let mut i = 0;
+        while i < items.len() {
+            self.blank_lines_before(&items[i]);
+            let mut uses = Vec::new();
+            if rearrange {
+                // A run of `use` items ends at any other item, or at a blank
+                // line carried over from the original source.
+                while let Some(Item::Use(item)) = items.get(i) {
+                    if !uses.is_empty() && self.has_blank_lines_before(&items[i]) {
+                        break;
+                    }
+                    uses.push(item);
+                    i += 1;
+                }
+            }
+            if uses.is_empty() {
+                self.item(&items[i]);
+                i += 1;
+            } else {
+                self.use_items(&uses);
+            }
+        }
+    }
+
+    fn use_items(&mut self, items: &[&ItemUse]) {
+        let mut uses = match self.options().imports_granularity {
+            ImportsGranularity::Preserve => items
+                .iter()
+                .map(|item| (*item, copy_use_tree(&item.tree)))
+                .collect(),
+            granularity => merge(items, granularity),
+        };
+        if self.options().reorder_imports {
+            for (_item, tree) in &mut uses {
+                sort_use_tree(tree);
+            }
+            uses.sort_by(|(a_item, a), (b_item, b)| {
+                let a = segments(a_item.leading_colon.is_some(), a);
+                let b = segments(b_item.leading_colon.is_some(), b);
+                cmp_segments(&a, &b)
+            });
+        }
+        for (item, tree) in &uses {
+            self.item_use_tree(item, tree);
+        }
+    }
+}
+
+// Regroup the names imported by a run of `use` items according to the
+// granularity. Like in rustfmt, items with attributes are never merged with
+// other items, and are only split up for `Item` granularity. Names are only
+// merged between items with the same visibility. The attributes and visibility
+// of each returned tree are those of the accompanying item.
+fn merge<'a>(
+    items: &[&'a ItemUse],
+    granularity: ImportsGranularity,
+) -> Vec<(&'a ItemUse, UseTree)> {
+    enum Entry<'a> {
+        Unchanged(&'a ItemUse),
+        Merged(usize),
+    }
+
+    struct Group<'a> {
+        item: &'a ItemUse,
+        key: Vec<String>,
+        imports: Vec<Import>,
+    }
+
+    let mut entries = Vec::new();
+    let mut groups: Vec<Group> = Vec::new();
+    for item in items {
+        if !item.attrs.is_empty() && granularity != ImportsGranularity::Item {
+            entries.push(Entry::Unchanged(item));
+            continue;
+        }
+        let mut imports = Vec::new();
+        flatten(&item.tree, &mut Vec::new(), &mut imports);
+        for import in imports {
+            let key = match granularity {
+                ImportsGranularity::Crate => import.path().take(1).collect(),
+                // `use a;` goes with the imports from module `a`.
+                ImportsGranularity::Module if import.module.is_empty() => import.path().collect(),
+                ImportsGranularity::Module => import.module.iter().map(Ident::to_string).collect(),
+                // A renamed import is an item of its own, apart from the
+                // same name imported without a rename.
+                ImportsGranularity::Item => match &import.leaf {
+                    Leaf::Rename(_, rename) => import
+                        .path()
+                        .chain(Some(format!("as {}", rename)))
+                        .collect(),
+                    Leaf::Name(_) | Leaf::Glob => import.path().collect(),
+                },
+                ImportsGranularity::One | ImportsGranularity::Preserve => Vec::new(),
+            };
+            let existing = groups.iter().position(|group| {
+                group.key == key
+                    && (ptr::eq(group.item, *item)
+                        || group.item.attrs.is_empty() && item.attrs.is_empty())
+                    && group.item.leading_colon.is_some() == item.leading_colon.is_some()
+                    && same_visibility(&group.item.vis, &item.vis)
+            });
+            let index = match existing {
+                Some(index) => index,
+                None => {
+                    entries.push(Entry::Merged(groups.len()));
+                    groups.push(Group {
+                        item,
+                        key,
+                        imports: Vec::new(),
+                    });
+                    groups.len() - 1
+                }
+            };
+            groups[index].imports.push(import);
+        }
+    }
+
+    let mut groups: Vec<Option<Group>> = groups.into_iter().map(Some).collect();
+    let mut uses = Vec::new();
+    for entry in entries {
+        match entry {
+            Entry::Unchanged(item) => uses.push((item, copy_use_tree(&item.tree))),
+            Entry::Merged(index) => {
+                let group = groups[index].take().unwrap();
+                let mut root = Node::default();
+                for import in group.imports {
+                    root.insert(import);
+                }
+                root.merge_self_imports();
+                let mut trees = root.into_use_trees();
+                if granularity == ImportsGranularity::One && trees.len() > 1 {
+                    trees = vec![UseTree::Group(UseGroup {
+                        brace_token: token::Brace::default(),
+                        items: trees.into_iter().collect(),
+                    })];
+                }
+                uses.extend(trees.into_iter().map(|tree| (group.item, tree)));
+            }
+        }
+    }
+    uses
+}
+
+// A single name imported by a use tree, along with the path of the module it
+// is imported from.
+struct Import {
+    module: Vec<Ident>,
+    leaf: Leaf,
+}
+
+enum Leaf {
+    Name(Ident),
+    Rename(Ident, Ident),
+    Glob,
+}
+
+impl Import {
+    fn path(&self) -> impl Iterator<Item = String> + '_ {
+        let leaf = match &self.leaf {
+            Leaf::Name(ident) | Leaf::Rename(ident, _) => ident.to_string(),
+            Leaf::Glob => "*".to_owned(),
+        };
+        self.module.iter().map(Ident::to_string).chain(Some(leaf))
+    }
+}
+
+fn flatten(tree: &UseTree, module: &mut Vec<Ident>, imports: &mut Vec<Import>) {
+    let leaf = match tree {
+        UseTree::Path(use_path) => {
+            module.push(use_path.ident.clone());
+            flatten(&use_path.tree, module, imports);
+            module.pop();
+            return;
+        }
+        UseTree::Group(use_group) => {
+            for tree in &use_group.items {
+                flatten(tree, module, imports);
+            }
+            return;
+        }
+        UseTree::Name(use_name) => Leaf::Name(use_name.ident.clone()),
+        UseTree::Rename(use_rename) => {
+            Leaf::Rename(use_rename.ident.clone(), use_rename.rename.clone())
+        }
+        UseTree::Glob(_) => Leaf::Glob,
+    };
+    imports.push(Import {
+        module: module.clone(),
+        leaf,
+    });
+}
+
+// Imports sharing a module path merged into a tree, in the order they were
+// first seen.
+#[derive(Default)]
+struct Node {
+    leaves: Vec<Leaf>,
+    children: Vec<(Ident, Node)>,
+}
+
+impl Node {
+    fn insert(&mut self, import: Import) {
+        let mut node = self;
+        for ident in import.module {
+            let index = match node.children.iter().position(|(name, _)| *name == ident) {
+                Some(index) => index,
+                None => {
+                    node.children.push((ident, Node::default()));
+                    node.children.len() - 1
+                }
+            };
+            node = &mut node.children[index].1;
+        }
+        let duplicate = node.leaves.iter().any(|leaf| match (leaf, &import.leaf) {
+            (Leaf::Name(a), Leaf::Name(b)) => a == b,
+            (Leaf::Rename(a, x), Leaf::Rename(b, y)) => a == b && x == y,
+            (Leaf::Glob, Leaf::Glob) => true,
+            _ => false,
+        });
+        if !duplicate {
+            node.leaves.push(import.leaf);
+        }
+    }
+
+    // Like rustfmt, turn `use a;` and `use a::b;` into `use a::{self, b};`,
+    // and `use a::b;` and `use a::b::c;` into `use a::b::{self, c};`, at any
+    // depth of the shared path.
+    fn merge_self_imports(&mut self) {
+        let children = &mut self.children;
+        self.leaves.retain(|leaf| {
+            let ident = match leaf {
+                Leaf::Name(ident) => ident,
+                Leaf::Rename(..) | Leaf::Glob => return true,
+            };
+            let child = match children.iter_mut().find(|(name, _)| name == ident) {
+                Some((_name, child)) => child,
+                None => return true,
+            };
+            let has_self = child.leaves.iter().any(|leaf| match leaf {
+                Leaf::Name(ident) => ident == "self",
+                Leaf::Rename(..) | Leaf::Glob => false,
+            });
+            if !has_self {
+                let self_ident = Ident::new("self", ident.span());
+                child.leaves.insert(0, Leaf::Name(self_ident));
+            }
+            false
+        });
+        for (_name, child) in &mut self.children {
+            child.merge_self_imports();
+        }
+    }
+
+    fn into_use_trees(self) -> Vec<UseTree> {
+        let mut trees = Vec::new();
+        for leaf in self.leaves {
+            trees.push(match leaf {
+                Leaf::Name(ident) => UseTree::Name(UseName { ident }),
+                Leaf::Rename(ident, rename) => UseTree::Rename(UseRename {
+                    ident,
+                    as_token: token::As::default(),
+                    rename,
+                }),
+                Leaf::Glob => UseTree::Glob(UseGlob {
+                    star_token: token::Star::default(),
+                }),
+            });
+        }
+        for (ident, child) in self.children {
+            let mut items = child.into_use_trees();
+            let tree = if items.len() == 1 && !is_self(&items[0]) {
+                items.pop().unwrap()
+            } else {
+                UseTree::Group(UseGroup {
+                    brace_token: token::Brace::default(),
+                    items: items.into_iter().collect(),
+                })
+            };
+            trees.push(UseTree::Path(UsePath {
+                ident,
+                colon2_token: token::PathSep::default(),
+                tree: Box::new(tree),
+            }));
+        }
+        trees
+    }
+}
+
+fn copy_use_tree(tree: &UseTree) -> UseTree {
+    match tree {
+        UseTree::Path(use_path) => UseTree::Path(UsePath {
+            ident: use_path.ident.clone(),
+            colon2_token: token::PathSep {
+                spans: use_path.colon2_token.spans,
+            },
+            tree: Box::new(copy_use_tree(&use_path.tree)),
+        }),
+        UseTree::Name(use_name) => UseTree::Name(UseName {
+            ident: use_name.ident.clone(),
+        }),
+        UseTree::Rename(use_rename) => UseTree::Rename(UseRename {
+            ident: use_rename.ident.clone(),
+            as_token: token::As {
+                span: use_rename.as_token.span,
+            },
+            rename: use_rename.rename.clone(),
+        }),
+        UseTree::Glob(use_glob) => UseTree::Glob(UseGlob {
+            star_token: token::Star {
+                spans: use_glob.star_token.spans,
+            },
+        }),
+        UseTree::Group(use_group) => UseTree::Group(UseGroup {
+            brace_token: token::Brace {
+                span: use_group.brace_token.span,
+            },
+            items: use_group.items.iter().map(copy_use_tree).collect(),
+        }),
+    }
+}
+
+// Whether the use tree is `self`, which is only allowed within braces, as in
+// `use a::{self};`.
+pub fn is_self(use_tree: &UseTree) -> bool {
+    match use_tree {
+        UseTree::Name(use_name) => use_name.ident == "self",
+        UseTree::Rename(use_rename) => use_rename.ident == "self",
+        _ => false,
+    }
+}
+
+fn same_visibility(a: &Visibility, b: &Visibility) -> bool {
+    match (a, b) {
+        (Visibility::Public(_), Visibility::Public(_))
+        | (Visibility::Inherited, Visibility::Inherited) => true,
+        (Visibility::Restricted(a), Visibility::Restricted(b)) => {
+            a.in_token.is_some() == b.in_token.is_some() && same_path(&a.path, &b.path)
+        }
+        _ => false,
+    }
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    a.leading_colon.is_some() == b.leading_colon.is_some()
+        && a.segments.len() == b.segments.len()
+        && a.segments
+            .iter()
+            .zip(&b.segments)
+            .all(|(a, b)| a.ident == b.ident)
+}
+
+// Sort the names within each braced group, with braces around a single name
+// removed, the way rustfmt does.
+fn sort_use_tree(tree: &mut UseTree) {
+    match tree {
+        UseTree::Path(use_path) => {
+            sort_use_tree(&mut use_path.tree);
+        }
+        UseTree::Group(use_group) => {
+            let mut items: Vec<UseTree> = mem::replace(&mut use_group.items, Punctuated::new())
+                .into_iter()
+                .collect();
+            for item in &mut items {
+                sort_use_tree(item);
+            }
+            items.sort_by(cmp_use_tree);
+            if items.len() == 1 && !is_self(&items[0]) {
+                *tree = items.pop().unwrap();
+            } else {
+                use_group.items = items.into_iter().collect();
+            }
+        }
+        UseTree::Name(_) | UseTree::Rename(_) | UseTree::Glob(_) => {}
+    }
+}
+
+enum Segment<'a> {
+    Slf(Option<&'a Ident>),
+    Super(Option<&'a Ident>),
+    Crate(Option<&'a Ident>),
+    // The leading `::` of `use ::a;`, which rustfmt sorts like a name that is
+    // empty.
+    Root,
+    Ident(&'a Ident, Option<&'a Ident>),
+    Glob,
+    List(&'a UseGroup),
+}
+
+impl<'a> Segment<'a> {
+    fn new(ident: &'a Ident, rename: Option<&'a Ident>) -> Self {
+        if ident == "self" {
+            Segment::Slf(rename)
+        } else if ident == "super" {
+            Segment::Super(rename)
+        } else if ident == "crate" {
+            Segment::Crate(rename)
+        } else {
+            Segment::Ident(ident, rename)
+        }
+    }
+
+    fn without_rename(&self) -> Self {
+        match *self {
+            Segment::Slf(_) => Segment::Slf(None),
+            Segment::Super(_) => Segment::Super(None),
+            Segment::Crate(_) => Segment::Crate(None),
+            Segment::Root => Segment::Root,
+            Segment::Ident(ident, _) => Segment::Ident(ident, None),
+            Segment::Glob => Segment::Glob,
+            Segment::List(use_group) => Segment::List(use_group),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Segment::Slf(_) => 0,
+            Segment::Super(_) => 1,
+            Segment::Crate(_) => 2,
+            Segment::Root => 3,
+            Segment::Ident(..) => 4,
+            Segment::Glob => 5,
+            Segment::List(_) => 6,
+        }
+    }
+}
+
+fn segments(leading_colon: bool, mut tree: &UseTree) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    if leading_colon {
+        segments.push(Segment::Root);
+    }
+    loop {
+        match tree {
+            UseTree::Path(use_path) => {
+                segments.push(Segment::new(&use_path.ident, None));
+                tree = &use_path.tree;
+            }
+            UseTree::Name(use_name) => {
+                segments.push(Segment::new(&use_name.ident, None));
+                return segments;
+            }
+            UseTree::Rename(use_rename) => {
+                segments.push(Segment::new(&use_rename.ident, Some(&use_rename.rename)));
+                return segments;
+            }
+            UseTree::Glob(_) => {
+                segments.push(Segment::Glob);
+                return segments;
+            }
+            UseTree::Group(use_group) => {
+                segments.push(Segment::List(use_group));
+                return segments;
+            }
+        }
+    }
+}
+
+// Ordering of use trees from rustfmt's style edition 2024: `self`, `super` and
+// `crate` ahead of other names, followed by globs and then braced groups, with
+// names in version sort order.
+fn cmp_use_tree(a: &UseTree, b: &UseTree) -> Ordering {
+    cmp_segments(&segments(false, a), &segments(false, b))
+}
+
+fn cmp_segments(a: &[Segment], b: &[Segment]) -> Ordering {
+    for (a, b) in a.iter().zip(b) {
+        // A difference only in renaming does not decide the order, so that
+        // `a as b` is placed ahead of the longer `a::c`.
+        let ordering = cmp_segment(a, b);
+        if ordering != Ordering::Equal
+            && cmp_segment(&a.without_rename(), &b.without_rename()) != Ordering::Equal
+        {
+            return ordering;
+        }
+    }
+    // Names that differ only in renaming, as in `a as b` and `a`, are ordered
+    // by their renames.
+    a.len().cmp(&b.len()).then_with(|| {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| cmp_segment(a, b))
+            .find(|ordering| *ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    })
+}
+
+fn cmp_segment(a: &Segment, b: &Segment) -> Ordering {
+    match (a, b) {
+        (Segment::Slf(a), Segment::Slf(b))
+        | (Segment::Super(a), Segment::Super(b))
+        | (Segment::Crate(a), Segment::Crate(b)) => cmp_rename(*a, *b),
+        (Segment::Ident(a, a_rename), Segment::Ident(b, b_rename)) => {
+            cmp_ident(a, b).then_with(|| cmp_rename(*a_rename, *b_rename))
+        }
+        (Segment::Root, Segment::Root) | (Segment::Glob, Segment::Glob) => Ordering::Equal,
+        (Segment::List(a), Segment::List(b)) => {
+            for (a, b) in a.items.iter().zip(&b.items) {
+                let ordering = cmp_use_tree(a, b);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            a.items.len().cmp(&b.items.len())
+        }
+        _ => a.rank().cmp(&b.rank()),
+    }
+}
+
+fn cmp_rename(a: Option<&Ident>, b: Option<&Ident>) -> Ordering {
+    match (a, b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(a), Some(b)) => cmp_ident(a, b),
+    }
+}
+
+fn cmp_ident(a: &Ident, b: &Ident) -> Ordering {
+    let a = a.to_string();
+    let b = b.to_string();
+    let a = a.strip_prefix("r#").unwrap_or(&a);
+    let b = b.strip_prefix("r#").unwrap_or(&b);
+    version_sort(a, b)
+}
+
+enum Chunk<'a> {
+    Underscore,
+    Str(&'a str),
+    Number(&'a str),
+}
+
+fn chunks(mut string: &str) -> impl Iterator<Item = Chunk<'_>> {
+    std::iter::from_fn(move || {
+        let first = string.chars().next()?;
+        let len = if first == '_' {
+            1
+        } else if first.is_ascii_digit() {
+            string
+                .find(|ch: char| !ch.is_ascii_digit())
+                .unwrap_or(string.len())
+        } else {
+            string
+                .find(|ch: char| ch == '_' || ch.is_ascii_digit())
+                .unwrap_or(string.len())
+        };
+        let (chunk, rest) = string.split_at(len);
+        string = rest;
+        Some(if first == '_' {
+            Chunk::Underscore
+        } else if first.is_ascii_digit() {
+            Chunk::Number(chunk)
+        } else {
+            Chunk::Str(chunk)
+        })
+    })
+}
+
+// Version sorting from the Rust style guide: strings are compared in chunks,
+// where underscores sort ahead of everything else and runs of digits compare
+// by their numeric value. Among numbers of equal value, the one with more
+// leading zeros goes first, but only as a tiebreaker after the rest of the
+// string.
+fn version_sort(a: &str, b: &str) -> Ordering {
+    let mut a_chunks = chunks(a);
+    let mut b_chunks = chunks(b);
+    let mut tiebreaker = Ordering::Equal;
+    loop {
+        let (a, b) = match (a_chunks.next(), b_chunks.next()) {
+            (Some(a), Some(b)) => (a, b),
+            (None, None) => return tiebreaker,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+        };
+        let ordering = match (a, b) {
+            (Chunk::Underscore, Chunk::Underscore) => Ordering::Equal,
+            (Chunk::Underscore, _) => Ordering::Less,
+            (_, Chunk::Underscore) => Ordering::Greater,
+            (Chunk::Number(a), Chunk::Number(b)) => {
+                let a_value = a.trim_start_matches('0');
+                let b_value = b.trim_start_matches('0');
+                let ordering = a_value
+                    .len()
+                    .cmp(&b_value.len())
+                    .then_with(|| a_value.cmp(b_value));
+                if ordering == Ordering::Equal && tiebreaker == Ordering::Equal {
+                    tiebreaker = b.len().cmp(&a.len());
+                }
+                ordering
+            }
+            (Chunk::Str(a) | Chunk::Number(a), Chunk::Str(b) | Chunk::Number(b)) => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use crate::algorithm::Printer;
+use crate::attr;
+use crate::fixup::FixupContext;
+use crate::imports;
 use crate::iter::IterDelimited;
 use crate::path::PathKind;
-use crate::INDENT;
+use crate::{UnparseError, INDENT};
 use proc_macro2::TokenStream;
 use syn::{
     Fields, FnArg, ForeignItem, ForeignItemFn, ForeignItemMacro, ForeignItemStatic,
@@ -12,7 +15,7 @@ use syn::{
     TraitItemType, Type, UseGlob, UseGroup, UseName, UsePath, UseRename, UseTree, Variadic,
 };
 
-impl Printer {
+impl Printer<'_> {
     pub fn item(&mut self, item: &Item) {
         match item {
             Item::Const(item) => self.item_const(item),
@@ -32,7 +35,7 @@ impl Printer {
             Item::Use(item) => self.item_use(item),
             Item::Verbatim(item) => self.item_verbatim(item),
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown Item"),
+            _ => self.unsupported(UnparseError::unknown("Item")),
         }
     }
 
@@ -47,7 +50,7 @@ impl Printer {
         self.ty(&item.ty);
         self.word(" = ");
         self.neverbreak();
-        self.expr(&item.expr);
+        self.expr(&item.expr, FixupContext::NONE);
         self.word(";");
         self.end();
         self.hardbreak();
@@ -60,14 +63,17 @@ impl Printer {
         self.word("enum ");
         self.ident(&item.ident);
         self.generics(&item.generics);
-        self.where_clause_for_body(&item.generics.where_clause);
+        let empty_body = item.variants.is_empty();
+        self.where_clause_for_body(&item.generics.where_clause, empty_body);
         self.word("{");
         self.hardbreak_if_nonempty();
         for variant in &item.variants {
+            self.blank_lines_before(variant);
             self.variant(variant);
             self.word(",");
             self.hardbreak();
         }
+        self.comments_before_close(&item.brace_token);
         self.offset(-INDENT);
         self.end();
         self.word("}");
@@ -92,13 +98,16 @@ impl Printer {
         self.cbox(INDENT);
         self.visibility(&item.vis);
         self.signature(&item.sig);
-        self.where_clause_for_body(&item.sig.generics.where_clause);
+        let empty_body = item.block.stmts.is_empty() && !attr::has_inner(&item.attrs);
+        self.where_clause_for_body(&item.sig.generics.where_clause, empty_body);
         self.word("{");
         self.hardbreak_if_nonempty();
         self.inner_attrs(&item.attrs);
         for stmt in &item.block.stmts {
+            self.blank_lines_before(stmt);
             self.stmt(stmt);
         }
+        self.comments_before_close(&item.block.brace_token);
         self.offset(-INDENT);
         self.end();
         self.word("}");
@@ -116,8 +125,10 @@ impl Printer {
         self.hardbreak_if_nonempty();
         self.inner_attrs(&item.attrs);
         for foreign_item in &item.items {
+            self.blank_lines_before(foreign_item);
             self.foreign_item(foreign_item);
         }
+        self.comments_before_close(&item.brace_token);
         self.offset(-INDENT);
         self.end();
         self.word("}");
@@ -149,13 +160,16 @@ impl Printer {
         }
         self.ty(&item.self_ty);
         self.end();
-        self.where_clause_for_body(&item.generics.where_clause);
+        let empty_body = item.items.is_empty() && !attr::has_inner(&item.attrs);
+        self.where_clause_for_body(&item.generics.where_clause, empty_body);
         self.word("{");
         self.hardbreak_if_nonempty();
         self.inner_attrs(&item.attrs);
         for impl_item in &item.items {
+            self.blank_lines_before(impl_item);
             self.impl_item(impl_item);
         }
+        self.comments_before_close(&item.brace_token);
         self.offset(-INDENT);
         self.end();
         self.word("}");
@@ -178,13 +192,12 @@ impl Printer {
         }
         self.word("mod ");
         self.ident(&item.ident);
-        if let Some((_brace, items)) = &item.content {
+        if let Some((brace, items)) = &item.content {
             self.word(" {");
             self.hardbreak_if_nonempty();
             self.inner_attrs(&item.attrs);
-            for item in items {
-                self.item(item);
-            }
+            self.items(items);
+            self.comments_before_close(brace);
             self.offset(-INDENT);
             self.end();
             self.word("}");
@@ -206,7 +219,7 @@ impl Printer {
         self.ty(&item.ty);
         self.word(" = ");
         self.neverbreak();
-        self.expr(&item.expr);
+        self.expr(&item.expr, FixupContext::NONE);
         self.word(";");
         self.end();
         self.hardbreak();
@@ -221,14 +234,17 @@ impl Printer {
         self.generics(&item.generics);
         match &item.fields {
             Fields::Named(fields) => {
-                self.where_clause_for_body(&item.generics.where_clause);
+                let empty_body = fields.named.is_empty();
+                self.where_clause_for_body(&item.generics.where_clause, empty_body);
                 self.word("{");
                 self.hardbreak_if_nonempty();
                 for field in &fields.named {
+                    self.blank_lines_before(field);
                     self.field(field);
                     self.word(",");
                     self.hardbreak();
                 }
+                self.comments_before_close(&fields.brace_token);
                 self.offset(-INDENT);
                 self.end();
                 self.word("}");
@@ -267,13 +283,16 @@ impl Printer {
             }
             self.type_param_bound(&supertrait);
         }
-        self.where_clause_for_body(&item.generics.where_clause);
+        let empty_body = item.items.is_empty() && !attr::has_inner(&item.attrs);
+        self.where_clause_for_body(&item.generics.where_clause, empty_body);
         self.word("{");
         self.hardbreak_if_nonempty();
         self.inner_attrs(&item.attrs);
         for trait_item in &item.items {
+            self.blank_lines_before(trait_item);
             self.trait_item(trait_item);
         }
+        self.comments_before_close(&item.brace_token);
         self.offset(-INDENT);
         self.end();
         self.word("}");
@@ -326,14 +345,17 @@ impl Printer {
         self.word("union ");
         self.ident(&item.ident);
         self.generics(&item.generics);
-        self.where_clause_for_body(&item.generics.where_clause);
+        let empty_body = item.fields.named.is_empty();
+        self.where_clause_for_body(&item.generics.where_clause, empty_body);
         self.word("{");
         self.hardbreak_if_nonempty();
         for field in &item.fields.named {
+            self.blank_lines_before(field);
             self.field(field);
             self.word(",");
             self.hardbreak();
         }
+        self.comments_before_close(&item.fields.brace_token);
         self.offset(-INDENT);
         self.end();
         self.word("}");
@@ -341,13 +363,17 @@ impl Printer {
     }
 
     fn item_use(&mut self, item: &ItemUse) {
+        self.item_use_tree(item, &item.tree);
+    }
+
+    pub fn item_use_tree(&mut self, item: &ItemUse, use_tree: &UseTree) {
         self.outer_attrs(&item.attrs);
         self.visibility(&item.vis);
         self.word("use ");
         if item.leading_colon.is_some() {
             self.word("::");
         }
-        self.use_tree(&item.tree);
+        self.use_tree(use_tree);
         self.word(";");
         self.hardbreak();
     }
@@ -355,7 +381,7 @@ impl Printer {
     #[cfg(not(feature = "verbatim"))]
     fn item_verbatim(&mut self, item: &TokenStream) {
         if !item.is_empty() {
-            unimplemented!("Item::Verbatim `{}`", item);
+            self.unsupported_verbatim("Item::Verbatim", item);
         }
         self.hardbreak();
     }
@@ -565,7 +591,11 @@ impl Printer {
 
         let item: ItemVerbatim = match syn::parse2(tokens.clone()) {
             Ok(item) => item,
-            Err(_) => unimplemented!("Item::Verbatim `{}`", tokens),
+            Err(_) => {
+                self.unsupported_verbatim("Item::Verbatim", tokens);
+                self.hardbreak();
+                return;
+            }
         };
 
         match item {
@@ -609,7 +639,8 @@ impl Printer {
                 }
                 self.ty(&item.self_ty);
                 self.end();
-                self.where_clause_for_body(&item.generics.where_clause);
+                let empty_body = item.items.is_empty() && !attr::has_inner(&item.attrs);
+                self.where_clause_for_body(&item.generics.where_clause, empty_body);
                 self.word("{");
                 self.hardbreak_if_nonempty();
                 self.inner_attrs(&item.attrs);
@@ -735,7 +766,7 @@ impl Printer {
     fn use_group(&mut self, use_group: &UseGroup) {
         if use_group.items.is_empty() {
             self.word("{}");
-        } else if use_group.items.len() == 1 {
+        } else if use_group.items.len() == 1 && !imports::is_self(&use_group.items[0]) {
             self.use_tree(&use_group.items[0]);
         } else {
             self.cbox(INDENT);
@@ -773,7 +804,7 @@ impl Printer {
             ForeignItem::Macro(item) => self.foreign_item_macro(item),
             ForeignItem::Verbatim(item) => self.foreign_item_verbatim(item),
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown ForeignItem"),
+            _ => self.unsupported(UnparseError::unknown("ForeignItem")),
         }
     }
 
@@ -823,7 +854,7 @@ impl Printer {
     #[cfg(not(feature = "verbatim"))]
     fn foreign_item_verbatim(&mut self, foreign_item: &TokenStream) {
         if !foreign_item.is_empty() {
-            unimplemented!("ForeignItem::Verbatim `{}`", foreign_item);
+            self.unsupported_verbatim("ForeignItem::Verbatim", foreign_item);
         }
         self.hardbreak();
     }
@@ -880,7 +911,11 @@ impl Printer {
 
         let foreign_item: ForeignItemVerbatim = match syn::parse2(tokens.clone()) {
             Ok(foreign_item) => foreign_item,
-            Err(_) => unimplemented!("ForeignItem::Verbatim `{}`", tokens),
+            Err(_) => {
+                self.unsupported_verbatim("ForeignItem::Verbatim", tokens);
+                self.hardbreak();
+                return;
+            }
         };
 
         match foreign_item {
@@ -907,7 +942,7 @@ impl Printer {
             TraitItem::Macro(item) => self.trait_item_macro(item),
             TraitItem::Verbatim(item) => self.trait_item_verbatim(item),
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown TraitItem"),
+            _ => self.unsupported(UnparseError::unknown("TraitItem")),
         }
     }
 
@@ -922,7 +957,7 @@ impl Printer {
         if let Some((_eq_token, default)) = &trait_item.default {
             self.word(" = ");
             self.neverbreak();
-            self.expr(default);
+            self.expr(default, FixupContext::NONE);
         }
         self.word(";");
         self.end();
@@ -934,13 +969,16 @@ impl Printer {
         self.cbox(INDENT);
         self.signature(&trait_item.sig);
         if let Some(block) = &trait_item.default {
-            self.where_clause_for_body(&trait_item.sig.generics.where_clause);
+            let empty_body = block.stmts.is_empty() && !attr::has_inner(&trait_item.attrs);
+            self.where_clause_for_body(&trait_item.sig.generics.where_clause, empty_body);
             self.word("{");
             self.hardbreak_if_nonempty();
             self.inner_attrs(&trait_item.attrs);
             for stmt in &block.stmts {
+                self.blank_lines_before(stmt);
                 self.stmt(stmt);
             }
+            self.comments_before_close(&block.brace_token);
             self.offset(-INDENT);
             self.end();
             self.word("}");
@@ -988,7 +1026,7 @@ impl Printer {
     #[cfg(not(feature = "verbatim"))]
     fn trait_item_verbatim(&mut self, trait_item: &TokenStream) {
         if !trait_item.is_empty() {
-            unimplemented!("TraitItem::Verbatim `{}`", trait_item);
+            self.unsupported_verbatim("TraitItem::Verbatim", trait_item);
         }
         self.hardbreak();
     }
@@ -1053,7 +1091,11 @@ impl Printer {
 
         let impl_item: TraitItemVerbatim = match syn::parse2(tokens.clone()) {
             Ok(impl_item) => impl_item,
-            Err(_) => unimplemented!("TraitItem::Verbatim `{}`", tokens),
+            Err(_) => {
+                self.unsupported_verbatim("TraitItem::Verbatim", tokens);
+                self.hardbreak();
+                return;
+            }
         };
 
         match impl_item {
@@ -1082,7 +1124,7 @@ impl Printer {
             ImplItem::Macro(item) => self.impl_item_macro(item),
             ImplItem::Verbatim(item) => self.impl_item_verbatim(item),
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown ImplItem"),
+            _ => self.unsupported(UnparseError::unknown("ImplItem")),
         }
     }
 
@@ -1100,7 +1142,7 @@ impl Printer {
         self.ty(&impl_item.ty);
         self.word(" = ");
         self.neverbreak();
-        self.expr(&impl_item.expr);
+        self.expr(&impl_item.expr, FixupContext::NONE);
         self.word(";");
         self.end();
         self.hardbreak();
@@ -1114,13 +1156,16 @@ impl Printer {
             self.word("default ");
         }
         self.signature(&impl_item.sig);
-        self.where_clause_for_body(&impl_item.sig.generics.where_clause);
+        let empty_body = impl_item.block.stmts.is_empty() && !attr::has_inner(&impl_item.attrs);
+        self.where_clause_for_body(&impl_item.sig.generics.where_clause, empty_body);
         self.word("{");
         self.hardbreak_if_nonempty();
         self.inner_attrs(&impl_item.attrs);
         for stmt in &impl_item.block.stmts {
+            self.blank_lines_before(stmt);
             self.stmt(stmt);
         }
+        self.comments_before_close(&impl_item.block.brace_token);
         self.offset(-INDENT);
         self.end();
         self.word("}");
@@ -1157,7 +1202,7 @@ impl Printer {
     #[cfg(not(feature = "verbatim"))]
     fn impl_item_verbatim(&mut self, impl_item: &TokenStream) {
         if !impl_item.is_empty() {
-            unimplemented!("ImplItem::Verbatim `{}`", impl_item);
+            self.unsupported_verbatim("ImplItem::Verbatim", impl_item);
         }
         self.hardbreak();
     }
@@ -1214,7 +1259,11 @@ impl Printer {
 
         let impl_item: ImplItemVerbatim = match syn::parse2(tokens.clone()) {
             Ok(impl_item) => impl_item,
-            Err(_) => unimplemented!("ImplItem::Verbatim `{}`", tokens),
+            Err(_) => {
+                self.unsupported_verbatim("ImplItem::Verbatim", tokens);
+                self.hardbreak();
+                return;
+            }
         };
 
         match impl_item {
@@ -1326,7 +1375,7 @@ impl Printer {
             StaticMutability::Mut(_) => self.word("mut "),
             StaticMutability::None => {}
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown StaticMutability"),
+            _ => self.unsupported(UnparseError::unknown("StaticMutability")),
         }
     }
 }
@@ -1334,6 +1383,8 @@ impl Printer {
 #[cfg(feature = "verbatim")]
 mod verbatim {
     use crate::algorithm::Printer;
+    use crate::attr;
+    use crate::fixup::FixupContext;
     use crate::iter::IterDelimited;
     use crate::INDENT;
     use syn::ext::IdentExt;
@@ -1545,7 +1596,7 @@ mod verbatim {
         }
     }
 
-    impl Printer {
+    impl Printer<'_> {
         pub fn flexible_item_const(&mut self, item: &FlexibleItemConst) {
             self.outer_attrs(&item.attrs);
             self.cbox(0);
@@ -1571,7 +1622,8 @@ mod verbatim {
             }
             self.signature(&item.sig);
             if let Some(body) = &item.body {
-                self.where_clause_for_body(&item.sig.generics.where_clause);
+                let empty_body = body.is_empty() && !attr::has_inner(&item.attrs);
+                self.where_clause_for_body(&item.sig.generics.where_clause, empty_body);
                 self.word("{");
                 self.hardbreak_if_nonempty();
                 self.inner_attrs(&item.attrs);
@@ -1602,7 +1654,7 @@ mod verbatim {
             if let Some(expr) = &item.expr {
                 self.word(" = ");
                 self.neverbreak();
-                self.expr(expr);
+                self.expr(expr, FixupContext::NONE);
             }
             self.word(";");
             self.end();
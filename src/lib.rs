@@ -322,6 +322,7 @@
 
 #![doc(html_root_url = "https://docs.rs/prettyplease/0.2.4")]
 #![allow(
+    clippy::bool_to_int_with_if,
     clippy::cast_possible_wrap,
     clippy::cast_sign_loss,
     clippy::derive_partial_eq_without_eq,
@@ -331,11 +332,15 @@
     clippy::let_underscore_untyped,
     clippy::match_like_matches_macro,
     clippy::match_same_arms,
+    clippy::missing_errors_doc,
     clippy::module_name_repetitions,
     clippy::must_use_candidate,
     clippy::needless_pass_by_value,
     clippy::similar_names,
+    clippy::single_match_else,
+    clippy::struct_excessive_bools,
     clippy::too_many_lines,
+    clippy::trivially_copy_pass_by_ref,
     clippy::unused_self,
     clippy::vec_init_then_push
 )]
@@ -343,37 +348,370 @@
 
 mod algorithm;
 mod attr;
+mod blank;
+mod classify;
+#[cfg(feature = "comments")]
+mod comments;
 mod convenience;
 mod data;
+mod error;
 mod expr;
 mod file;
+mod fixup;
 mod generics;
+mod imports;
 mod item;
 mod iter;
 mod lifetime;
 mod lit;
 mod mac;
+mod options;
 mod pat;
 mod path;
+mod precedence;
 mod ring;
+mod rustfmt;
+mod source_map;
 mod stmt;
 mod token;
 mod ty;
+#[cfg(feature = "unparen")]
+mod unparen;
+#[cfg(feature = "verify")]
+mod verify;
+mod width;
+mod writer;
 
 use crate::algorithm::Printer;
-use syn::File;
+use crate::fixup::FixupContext;
+use crate::writer::IoWriter;
+use std::fmt;
+use std::io;
+use syn::{Block, Expr, File, Item, Pat, Stmt, Type};
 
-// Target line width.
-const MARGIN: isize = 89;
+pub use crate::error::{UnparseError, WriteError};
+pub use crate::options::{
+    BraceStyle, ImportsGranularity, MatchArmLeadingPipes, NewlineStyle, Options, SmallHeuristics,
+    StrWidth,
+};
+pub use crate::rustfmt::{RustfmtConfig, RustfmtConfigError};
+pub use crate::source_map::{Mapping, SourceMap};
+#[cfg(feature = "verify")]
+pub use crate::verify::{unparse_verified, VerifyError};
 
-// Number of spaces increment at each level of block indentation.
+// Unit of indentation offset used throughout the token stream. The printer
+// rescales it to the configured `Options::tab_spaces` columns.
 const INDENT: isize = 4;
 
-// Every line is allowed at least this much space, even if highly indented.
-const MIN_SPACE: isize = 60;
-
 pub fn unparse(file: &File) -> String {
-    let mut p = Printer::new();
+    unparse_with(file, &Options::default())
+}
+
+/// Like [`unparse`], but formatted according to the given [`Options`].
+pub fn unparse_with(file: &File, options: &Options) -> String {
+    try_unparse_with(file, options).unwrap_or_else(|error| unimplemented!("{}", error))
+}
+
+/// Like [`unparse_with`], but keeps the `//` and `/* */` comments from the
+/// source text that `file` was parsed from, which are otherwise discarded by
+/// syn.
+///
+/// Each comment is printed on its own line ahead of the nearest item,
+/// statement, field, enum variant or match arm. Comments in other positions,
+/// such as in the middle of an expression, move to the start of the next such
+/// node within the same block or body, or to the end of the block or body if
+/// no such node follows them.
+///
+/// The syntax tree needs to have been parsed from `source` at runtime, for
+/// example by `syn::parse_file`, and not within a procedural macro. Spans in
+/// procedural macro input do not carry the line and column information needed
+/// to match up comments with syntax tree nodes.
+///
+/// Requires the `"comments"` feature.
+///
+/// ```
+/// let source = "fn main() {\n    // Say hello.\n    println!(\"hello\");\n\n    exit();\n}\n";
+/// let syntax_tree = syn::parse_file(source).unwrap();
+///
+/// let mut options = prettyplease::Options::default();
+/// options.blank_lines = 1;
+/// let formatted = prettyplease::unparse_with_comments(&syntax_tree, source, &options);
+/// assert_eq!(formatted, source);
+/// ```
+#[cfg(feature = "comments")]
+pub fn unparse_with_comments(file: &File, source: &str, options: &Options) -> String {
+    try_unparse_with_comments(file, source, options)
+        .unwrap_or_else(|error| unimplemented!("{}", error))
+}
+
+/// Like [`unparse_with_comments`], but returns an error instead of panicking
+/// if the syntax tree contains a node that prettyplease does not know how to
+/// print, as in [`try_unparse`].
+///
+/// Requires the `"comments"` feature.
+#[cfg(feature = "comments")]
+pub fn try_unparse_with_comments(
+    file: &File,
+    source: &str,
+    options: &Options,
+) -> Result<String, UnparseError> {
+    let mut file = file.clone();
+    let trailing = comments::attach(&mut file, source);
+    let mut p = Printer::new(options.clone());
+    p.set_source(source);
+    p.set_comments_before_close(trailing.braces);
+    p.file(&file);
+    p.outer_attrs(&trailing.file);
+    p.eof()
+}
+
+/// Like [`unparse_with`], but also returns the syntax tree nodes that were
+/// printed as plain tokens because of [`Options::verbatim_fallback`].
+///
+/// ```
+/// let mut syntax_tree: syn::File = syn::parse_str("fn main() {}").unwrap();
+/// let tokens = "unknown syntax;".parse().unwrap();
+/// syntax_tree.items.push(syn::Item::Verbatim(tokens));
+///
+/// let mut options = prettyplease::Options::default();
+/// options.verbatim_fallback = true;
+/// let (formatted, fallbacks) = prettyplease::unparse_with_report(&syntax_tree, &options);
+/// assert_eq!(formatted, "fn main() {}\nunknown syntax;\n");
+/// assert_eq!(fallbacks[0].kind(), "Item::Verbatim");
+/// ```
+pub fn unparse_with_report(file: &File, options: &Options) -> (String, Vec<UnparseError>) {
+    let mut p = Printer::new(options.clone());
+    p.file(file);
+    let fallbacks = p.take_fallbacks();
+    match p.eof() {
+        Ok(formatted) => (formatted, fallbacks),
+        Err(error) => unimplemented!("{}", error),
+    }
+}
+
+/// Like [`unparse_with`], but also returns a [`SourceMap`] from positions in
+/// the formatted output to the spans of the syntax tree tokens printed there.
+///
+/// This is useful for translating the location of a compiler error in
+/// formatted generated code back to the input of the code generator. Spans
+/// only carry line and column information outside of procedural macros if
+/// proc-macro2's `"span-locations"` feature is enabled.
+pub fn unparse_with_source_map(file: &File, options: &Options) -> (String, SourceMap) {
+    let mut p = Printer::new(options.clone());
+    p.record_source_map();
+    p.file(file);
+    p.eof_with_source_map()
+        .unwrap_or_else(|error| unimplemented!("{}", error))
+}
+
+/// Like [`unparse`], but returns an error instead of panicking if the syntax
+/// tree contains a node that prettyplease does not know how to print, such as
+/// an unrecognized `Verbatim` token stream.
+///
+/// ```
+/// let syntax_tree: syn::File = syn::parse_str("fn main() {}").unwrap();
+/// match prettyplease::try_unparse(&syntax_tree) {
+///     Ok(formatted) => print!("{}", formatted),
+///     Err(error) => eprintln!("failed to format {}: {}", error.kind(), error),
+/// }
+/// ```
+pub fn try_unparse(file: &File) -> Result<String, UnparseError> {
+    try_unparse_with(file, &Options::default())
+}
+
+/// Like [`try_unparse`], but formatted according to the given [`Options`].
+pub fn try_unparse_with(file: &File, options: &Options) -> Result<String, UnparseError> {
+    let mut p = Printer::new(options.clone());
     p.file(file);
     p.eof()
 }
+
+/// Like [`unparse`], but writes the formatted output to an [`io::Write`] as it
+/// is produced rather than collecting it into a `String`.
+///
+/// Output is written in chunks of complete lines, so there is no need to wrap
+/// the writer in a `BufWriter`. If an error is returned, part of the output may
+/// already have been written.
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// let syntax_tree: syn::File = syn::parse_str("fn main() {}").unwrap();
+/// let file = std::fs::File::create("main.rs")?;
+/// prettyplease::unparse_to_writer(&syntax_tree, file)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn unparse_to_writer(file: &File, writer: impl io::Write) -> Result<(), WriteError> {
+    unparse_to_writer_with(file, &Options::default(), writer)
+}
+
+/// Like [`unparse_to_writer`], but formatted according to the given
+/// [`Options`].
+///
+/// ```no_run
+/// # fn main() -> std::io::Result<()> {
+/// let syntax_tree: syn::File = syn::parse_str("fn main() {}").unwrap();
+/// let mut options = prettyplease::Options::default();
+/// options.newline_style = prettyplease::NewlineStyle::Windows;
+/// let file = std::fs::File::create("main.rs")?;
+/// prettyplease::unparse_to_writer_with(&syntax_tree, &options, file)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn unparse_to_writer_with(
+    file: &File,
+    options: &Options,
+    writer: impl io::Write,
+) -> Result<(), WriteError> {
+    let mut writer = IoWriter::new(writer);
+    match unparse_to_fmt_writer_with(file, options, &mut writer) {
+        Err(WriteError::Fmt(fmt::Error)) => Err(WriteError::Io(writer.into_error())),
+        result => result,
+    }
+}
+
+/// Like [`unparse_to_writer`], but for a [`fmt::Write`] such as a `String` or
+/// a `Formatter`.
+///
+/// ```
+/// let syntax_tree: syn::File = syn::parse_str("fn main() {}").unwrap();
+/// let mut formatted = String::new();
+/// prettyplease::unparse_to_fmt_writer(&syntax_tree, &mut formatted).unwrap();
+/// assert_eq!(formatted, "fn main() {}\n");
+/// ```
+pub fn unparse_to_fmt_writer(file: &File, writer: impl fmt::Write) -> Result<(), WriteError> {
+    unparse_to_fmt_writer_with(file, &Options::default(), writer)
+}
+
+/// Like [`unparse_to_fmt_writer`], but formatted according to the given
+/// [`Options`].
+///
+/// ```
+/// let syntax_tree: syn::File = syn::parse_str("fn main() {}").unwrap();
+/// let mut options = prettyplease::Options::default();
+/// options.newline_style = prettyplease::NewlineStyle::Windows;
+/// let mut formatted = String::new();
+/// prettyplease::unparse_to_fmt_writer_with(&syntax_tree, &options, &mut formatted).unwrap();
+/// assert_eq!(formatted, "fn main() {}\r\n");
+/// ```
+pub fn unparse_to_fmt_writer_with(
+    file: &File,
+    options: &Options,
+    mut writer: impl fmt::Write,
+) -> Result<(), WriteError> {
+    let mut p = Printer::with_sink(options.clone(), &mut writer);
+    p.file(file);
+    p.eof_to_sink()
+}
+
+/// Format a single item, such as a function or struct, the same way it would
+/// appear at the top level of a file.
+///
+/// Like [`unparse`], the output ends with a newline.
+///
+/// ```
+/// let item: syn::Item = syn::parse_str("struct S{x:u8}").unwrap();
+/// assert_eq!(prettyplease::unparse_item(&item), "struct S {\n    x: u8,\n}\n");
+/// ```
+pub fn unparse_item(item: &Item) -> String {
+    unparse_item_with(item, &Options::default())
+}
+
+/// Like [`unparse_item`], but formatted according to the given [`Options`].
+pub fn unparse_item_with(item: &Item, options: &Options) -> String {
+    unparse_fragment(options, |p| p.item(item))
+}
+
+/// Format a single expression.
+///
+/// The output does not end with a newline.
+///
+/// ```
+/// let expr: syn::Expr = syn::parse_str("a.b(|x| { x+1 })").unwrap();
+/// assert_eq!(prettyplease::unparse_expr(&expr), "a.b(|x| { x + 1 })");
+/// ```
+pub fn unparse_expr(expr: &Expr) -> String {
+    unparse_expr_with(expr, &Options::default())
+}
+
+/// Like [`unparse_expr`], but formatted according to the given [`Options`].
+///
+/// ```
+/// let expr: syn::Expr = syn::parse_str("f(first_argument, second_argument)").unwrap();
+/// let mut options = prettyplease::Options::default();
+/// options.max_width = 20;
+/// assert_eq!(
+///     prettyplease::unparse_expr_with(&expr, &options),
+///     "f(\n    first_argument,\n    second_argument,\n)",
+/// );
+/// ```
+pub fn unparse_expr_with(expr: &Expr, options: &Options) -> String {
+    unparse_fragment(options, |p| {
+        p.expr_beginning_of_line(expr, true, FixupContext::NONE);
+    })
+}
+
+/// Format a single type.
+///
+/// The output does not end with a newline.
+pub fn unparse_type(ty: &Type) -> String {
+    unparse_type_with(ty, &Options::default())
+}
+
+/// Like [`unparse_type`], but formatted according to the given [`Options`].
+pub fn unparse_type_with(ty: &Type, options: &Options) -> String {
+    unparse_fragment(options, |p| p.ty(ty))
+}
+
+/// Format a single pattern.
+///
+/// The output does not end with a newline.
+pub fn unparse_pat(pat: &Pat) -> String {
+    unparse_pat_with(pat, &Options::default())
+}
+
+/// Like [`unparse_pat`], but formatted according to the given [`Options`].
+pub fn unparse_pat_with(pat: &Pat, options: &Options) -> String {
+    unparse_fragment(options, |p| p.pat(pat))
+}
+
+/// Format a single statement, the same way it would appear inside a block.
+///
+/// Like an item, the output ends with a newline.
+///
+/// ```
+/// let stmt: syn::Stmt = syn::parse_str("let x=1;").unwrap();
+/// assert_eq!(prettyplease::unparse_stmt(&stmt), "let x = 1;\n");
+/// ```
+pub fn unparse_stmt(stmt: &Stmt) -> String {
+    unparse_stmt_with(stmt, &Options::default())
+}
+
+/// Like [`unparse_stmt`], but formatted according to the given [`Options`].
+pub fn unparse_stmt_with(stmt: &Stmt, options: &Options) -> String {
+    unparse_fragment(options, |p| p.stmt(stmt))
+}
+
+/// Format a block, including its surrounding braces.
+///
+/// The output ends with the closing brace, not with a newline.
+pub fn unparse_block(block: &Block) -> String {
+    unparse_block_with(block, &Options::default())
+}
+
+/// Like [`unparse_block`], but formatted according to the given [`Options`].
+pub fn unparse_block_with(block: &Block, options: &Options) -> String {
+    unparse_fragment(options, |p| {
+        p.cbox(INDENT);
+        p.small_block(block, &[]);
+        p.end();
+    })
+}
+
+fn unparse_fragment(options: &Options, print: impl FnOnce(&mut Printer)) -> String {
+    let mut p = Printer::new(options.clone());
+    p.cbox(0);
+    print(&mut p);
+    p.end();
+    p.eof().unwrap_or_else(|error| unimplemented!("{}", error))
+}
@@ -1,9 +1,9 @@
 use crate::algorithm::Printer;
 use syn::Lifetime;
 
-impl Printer {
+impl Printer<'_> {
     pub fn lifetime(&mut self, lifetime: &Lifetime) {
-        self.word("'");
+        self.word_spanned("'", lifetime.apostrophe);
         self.ident(&lifetime.ident);
     }
 }
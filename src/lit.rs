@@ -1,8 +1,9 @@
 use crate::algorithm::Printer;
+use crate::UnparseError;
 use proc_macro2::Literal;
 use syn::{Lit, LitBool, LitByte, LitByteStr, LitChar, LitFloat, LitInt, LitStr};
 
-impl Printer {
+impl Printer<'_> {
     pub fn lit(&mut self, lit: &Lit) {
         match lit {
             Lit::Str(lit) => self.lit_str(lit),
@@ -14,39 +15,39 @@ impl Printer {
             Lit::Bool(lit) => self.lit_bool(lit),
             Lit::Verbatim(lit) => self.lit_verbatim(lit),
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown Lit"),
+            _ => self.unsupported(UnparseError::unknown("Lit")),
         }
     }
 
     pub fn lit_str(&mut self, lit: &LitStr) {
-        self.word(lit.token().to_string());
+        self.word_spanned(lit.token().to_string(), lit.span());
     }
 
     fn lit_byte_str(&mut self, lit: &LitByteStr) {
-        self.word(lit.token().to_string());
+        self.word_spanned(lit.token().to_string(), lit.span());
     }
 
     fn lit_byte(&mut self, lit: &LitByte) {
-        self.word(lit.token().to_string());
+        self.word_spanned(lit.token().to_string(), lit.span());
     }
 
     fn lit_char(&mut self, lit: &LitChar) {
-        self.word(lit.token().to_string());
+        self.word_spanned(lit.token().to_string(), lit.span());
     }
 
     fn lit_int(&mut self, lit: &LitInt) {
-        self.word(lit.token().to_string());
+        self.word_spanned(lit.token().to_string(), lit.span());
     }
 
     fn lit_float(&mut self, lit: &LitFloat) {
-        self.word(lit.token().to_string());
+        self.word_spanned(lit.token().to_string(), lit.span());
     }
 
     fn lit_bool(&mut self, lit: &LitBool) {
-        self.word(if lit.value { "true" } else { "false" });
+        self.word_spanned(if lit.value { "true" } else { "false" }, lit.span);
     }
 
     fn lit_verbatim(&mut self, token: &Literal) {
-        self.word(token.to_string());
+        self.word_spanned(token.to_string(), token.span());
     }
 }
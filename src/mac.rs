@@ -1,11 +1,11 @@
 use crate::algorithm::Printer;
 use crate::path::PathKind;
 use crate::token::Token;
-use crate::INDENT;
+use crate::{UnparseError, INDENT};
 use proc_macro2::{Delimiter, Spacing, TokenStream};
 use syn::{Ident, Macro, MacroDelimiter};
 
-impl Printer {
+impl Printer<'_> {
     pub fn mac(&mut self, mac: &Macro, ident: Option<&Ident>) {
         if mac.path.is_ident("macro_rules") {
             if let Some(ident) = ident {
@@ -109,7 +109,11 @@ impl Printer {
                     self.hardbreak();
                     state = Start;
                 }
-                _ => unimplemented!("bad macro_rules syntax"),
+                _ => {
+                    self.unsupported(UnparseError::verbatim("macro_rules", rules));
+                    state = Start;
+                    break;
+                }
             }
         }
         match state {
@@ -206,6 +210,18 @@ impl Printer {
             state = next_state;
         }
     }
+
+    pub fn unsupported_verbatim(&mut self, kind: &'static str, tokens: &TokenStream) {
+        let error = UnparseError::verbatim(kind, tokens);
+        if self.options().verbatim_fallback {
+            self.ibox(0);
+            self.macro_rules_tokens(tokens.clone(), false);
+            self.end();
+            self.fallback(error);
+        } else {
+            self.unsupported(error);
+        }
+    }
 }
 
 fn is_keyword(ident: &Ident) -> bool {
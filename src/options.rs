@@ -0,0 +1,339 @@
+use crate::width;
+use std::fmt::{self, Debug};
+
+/// Formatting options accepted by [`unparse_with`][crate::unparse_with].
+///
+/// ```
+/// let mut options = prettyplease::Options::default();
+/// options.max_width = 100;
+/// options.tab_spaces = 2;
+/// ```
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Options {
+    /// Target line width.
+    ///
+    /// Default: 89
+    pub max_width: usize,
+
+    /// Number of spaces increment at each level of block indentation.
+    ///
+    /// Default: 4
+    pub tab_spaces: usize,
+
+    /// Indent with tab characters instead of spaces. Each tab counts as
+    /// [`tab_spaces`][Options::tab_spaces] columns toward the line width.
+    ///
+    /// Default: false
+    pub hard_tabs: bool,
+
+    /// Line ending to print at the end of each line, including the lines
+    /// within block comments and multi-line string literals, like rustfmt's
+    /// `newline_style`.
+    ///
+    /// Default: [`NewlineStyle::Unix`]
+    pub newline_style: NewlineStyle,
+
+    /// Every line is allowed at least this much space, even if highly
+    /// indented.
+    ///
+    /// Default: 60
+    pub min_space: usize,
+
+    /// How many columns of the line a token takes up, used to decide where
+    /// lines break.
+    ///
+    /// Default: the Unicode display width, counting East Asian wide
+    /// characters and emoji as two columns and combining marks as zero
+    pub str_width: StrWidth,
+
+    /// Whether the width limits below apply, like rustfmt's
+    /// `use_small_heuristics`.
+    ///
+    /// Default: [`SmallHeuristics::Default`]
+    pub use_small_heuristics: SmallHeuristics,
+
+    /// Maximum width of a struct literal, including its path, to be printed on
+    /// one line.
+    ///
+    /// Default: 34
+    pub struct_lit_width: usize,
+
+    /// Maximum width of the arguments of a function or method call to be
+    /// printed on one line.
+    ///
+    /// Default: no limit other than [`max_width`][Options::max_width]
+    pub fn_call_width: usize,
+
+    /// Maximum width of a chain of method calls to be printed on one line.
+    ///
+    /// Default: no limit other than [`max_width`][Options::max_width]
+    pub chain_width: usize,
+
+    /// Print a chain of two or more method calls, such as a builder, with
+    /// each call on its own line even if the whole chain would fit on one.
+    ///
+    /// Default: false
+    pub chain_one_call_per_line: bool,
+
+    /// Maximum width of the elements of an array literal to be printed on one
+    /// line.
+    ///
+    /// Default: no limit other than [`max_width`][Options::max_width]
+    pub array_width: usize,
+
+    /// Print `Verbatim` syntax tree nodes whose tokens are not recognized as
+    /// plain tokens, rather than failing. The affected nodes are listed by
+    /// [`unparse_with_report`][crate::unparse_with_report].
+    ///
+    /// Default: false
+    pub verbatim_fallback: bool,
+
+    /// Maximum number of consecutive blank lines to carry over from the
+    /// original source between items, impl members, struct fields, enum
+    /// variants and statements. Zero means none are kept.
+    ///
+    /// This relies on the line information in the syntax tree's spans, so the
+    /// syntax tree needs to have been parsed at runtime, for example by
+    /// `syn::parse_file`, rather than inside a procedural macro. Requires the
+    /// `"span-locations"` feature.
+    ///
+    /// Default: 0
+    #[cfg(feature = "span-locations")]
+    pub blank_lines: usize,
+
+    /// Leave out parentheses in the syntax tree that are not needed to
+    /// preserve its structure, such as the ones that code generators often put
+    /// around every interpolated expression. Parentheses required by operator
+    /// precedence are printed regardless. Parentheses in types and patterns
+    /// are only left out where they are unambiguously redundant.
+    ///
+    /// Requires the `"unparen"` feature.
+    ///
+    /// Default: false
+    #[cfg(feature = "unparen")]
+    pub remove_redundant_parens: bool,
+
+    /// Sort runs of consecutive `use` items, and the names within the braces
+    /// of each `use` item, in the order used by rustfmt's `reorder_imports`
+    /// with style edition 2024. A blank line kept by `blank_lines` ends a run.
+    ///
+    /// Default: false
+    pub reorder_imports: bool,
+
+    /// Merge or split runs of consecutive `use` items like rustfmt's
+    /// `imports_granularity`. Items with attributes are left as they are, and
+    /// only items with the same visibility are merged.
+    ///
+    /// Default: [`ImportsGranularity::Preserve`]
+    pub imports_granularity: ImportsGranularity,
+
+    /// Where to put the opening brace of the body of functions, structs,
+    /// enums, unions, traits and impl blocks, like rustfmt's `brace_style`.
+    ///
+    /// Default: [`BraceStyle::SameLineWhere`]
+    pub brace_style: BraceStyle,
+
+    /// Print a `where` clause that has a single predicate on one line with the
+    /// `where` keyword, like rustfmt's `where_single_line`.
+    ///
+    /// Default: false
+    pub where_single_line: bool,
+
+    /// Wrap the body of a match arm in braces when it does not fit on the
+    /// line with the pattern, like rustfmt's `match_arm_blocks`. If false,
+    /// the body goes on the next line by itself, followed by a comma, unless
+    /// it begins with a brace of its own on the `=>` line, such as a `match`,
+    /// `loop` or struct literal.
+    ///
+    /// Default: true
+    pub match_arm_blocks: bool,
+
+    /// Whether to print a `|` ahead of the first pattern of a match arm, like
+    /// rustfmt's `match_arm_leading_pipes`.
+    ///
+    /// Default: [`MatchArmLeadingPipes::Never`]
+    pub match_arm_leading_pipes: MatchArmLeadingPipes,
+
+    /// Print a comma after a match arm whose body is a block, like rustfmt's
+    /// `match_block_trailing_comma`.
+    ///
+    /// Default: false
+    pub match_block_trailing_comma: bool,
+
+    /// Refill the paragraphs of `///` and `//!` doc comments to
+    /// [`max_width`][Options::max_width]: adjacent lines of prose are joined,
+    /// stopping at blank lines, list items, headings and code fences, and then
+    /// broken into several lines at spaces, but not inside inline code or link
+    /// text. Wrapped list items continue at the indentation of their text.
+    /// Lines in code blocks, headings, tables and other markdown that would
+    /// not mean the same thing joined or split up are left alone, as are
+    /// `/** */` block doc comments.
+    ///
+    /// Default: false
+    pub wrap_doc_comments: bool,
+
+    /// Print doc attributes whose text spans several lines as consecutive
+    /// `///` or `//!` lines, rather than as a `/** */` block comment, like
+    /// rustfmt's `normalize_doc_attributes`. Each line of the text is printed
+    /// as it is right after the `///`, so that the value of the doc attribute
+    /// stays the same. Text that cannot be written this way, such as an outer
+    /// doc line starting with `/` or a `\r`, is printed as before.
+    ///
+    /// Default: false
+    pub normalize_doc_attributes: bool,
+}
+
+/// How to group the names imported by `use` items, for
+/// [`Options::imports_granularity`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ImportsGranularity {
+    /// Keep each `use` item as written.
+    Preserve,
+    /// One `use` item per crate: `use a::{b::c, d};`
+    Crate,
+    /// One `use` item per module: `use a::b::c;` and `use a::d;`
+    Module,
+    /// One `use` item per imported name.
+    Item,
+    /// A single `use` item for everything: `use {a::b, c::d};`
+    One,
+}
+
+/// Line ending for [`Options::newline_style`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NewlineStyle {
+    /// `\n`
+    Unix,
+    /// `\r\n`
+    Windows,
+    /// `\r\n` when compiled for Windows, otherwise `\n`.
+    Native,
+}
+
+/// Leading `|` of a match arm's pattern, for
+/// [`Options::match_arm_leading_pipes`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MatchArmLeadingPipes {
+    /// Leave out the leading `|`.
+    Never,
+    /// Print a leading `|` on every arm.
+    Always,
+    /// Print a leading `|` on the arms that have one in the syntax tree.
+    Preserve,
+}
+
+/// Whether to apply the width limits of [`Options::struct_lit_width`],
+/// [`fn_call_width`][Options::fn_call_width],
+/// [`chain_width`][Options::chain_width] and
+/// [`array_width`][Options::array_width].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SmallHeuristics {
+    /// Apply the width limits as configured.
+    Default,
+    /// Always print struct literals with fields across multiple lines, and
+    /// otherwise break lines only where they exceed [`Options::max_width`].
+    Off,
+    /// Let everything take up as much as [`Options::max_width`] on one line.
+    Max,
+}
+
+/// Placement of the opening brace of an item's body, for
+/// [`Options::brace_style`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BraceStyle {
+    /// On the same line as the signature, or on its own line after a `where`
+    /// clause.
+    SameLineWhere,
+    /// Always on its own line, except for an empty body: `{}`
+    AlwaysNextLine,
+}
+
+/// Function that measures the width of text in columns, for
+/// [`Options::str_width`].
+///
+/// ```
+/// let mut options = prettyplease::Options::default();
+/// // Count bytes instead, as if all text were ASCII.
+/// options.str_width = prettyplease::StrWidth(str::len);
+/// ```
+#[derive(Copy, Clone)]
+pub struct StrWidth(pub fn(&str) -> usize);
+
+impl Options {
+    // The width limit to use for one of the small heuristics, given the
+    // configured limit and the one that applies with SmallHeuristics::Off.
+    pub(crate) fn heuristic_width(&self, width: usize, off: usize) -> usize {
+        match self.use_small_heuristics {
+            SmallHeuristics::Default => width,
+            SmallHeuristics::Off => off,
+            SmallHeuristics::Max => usize::MAX,
+        }
+    }
+}
+
+impl NewlineStyle {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            NewlineStyle::Unix => "\n",
+            NewlineStyle::Windows => "\r\n",
+            NewlineStyle::Native => {
+                if cfg!(windows) {
+                    "\r\n"
+                } else {
+                    "\n"
+                }
+            }
+        }
+    }
+}
+
+impl Default for StrWidth {
+    fn default() -> Self {
+        StrWidth(width::display_width)
+    }
+}
+
+impl Debug for StrWidth {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("StrWidth(..)")
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            max_width: 89,
+            tab_spaces: 4,
+            hard_tabs: false,
+            newline_style: NewlineStyle::Unix,
+            min_space: 60,
+            str_width: StrWidth::default(),
+            use_small_heuristics: SmallHeuristics::Default,
+            struct_lit_width: 34,
+            fn_call_width: usize::MAX,
+            chain_width: usize::MAX,
+            chain_one_call_per_line: false,
+            array_width: usize::MAX,
+            verbatim_fallback: false,
+            #[cfg(feature = "span-locations")]
+            blank_lines: 0,
+            #[cfg(feature = "unparen")]
+            remove_redundant_parens: false,
+            reorder_imports: false,
+            imports_granularity: ImportsGranularity::Preserve,
+            brace_style: BraceStyle::SameLineWhere,
+            where_single_line: false,
+            match_arm_blocks: true,
+            match_arm_leading_pipes: MatchArmLeadingPipes::Never,
+            match_block_trailing_comma: false,
+            wrap_doc_comments: false,
+            normalize_doc_attributes: false,
+        }
+    }
+}
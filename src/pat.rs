@@ -1,14 +1,15 @@
 use crate::algorithm::Printer;
+use crate::fixup::FixupContext;
 use crate::iter::IterDelimited;
 use crate::path::PathKind;
-use crate::INDENT;
+use crate::{UnparseError, INDENT};
 use proc_macro2::TokenStream;
 use syn::{
     FieldPat, Pat, PatIdent, PatOr, PatParen, PatReference, PatRest, PatSlice, PatStruct, PatTuple,
     PatTupleStruct, PatType, PatWild,
 };
 
-impl Printer {
+impl Printer<'_> {
     pub fn pat(&mut self, pat: &Pat) {
         match pat {
             Pat::Const(pat) => self.expr_const(pat),
@@ -18,7 +19,7 @@ impl Printer {
             Pat::Or(pat) => self.pat_or(pat),
             Pat::Paren(pat) => self.pat_paren(pat),
             Pat::Path(pat) => self.expr_path(pat),
-            Pat::Range(pat) => self.expr_range(pat),
+            Pat::Range(pat) => self.expr_range(pat, FixupContext::NONE),
             Pat::Reference(pat) => self.pat_reference(pat),
             Pat::Rest(pat) => self.pat_rest(pat),
             Pat::Slice(pat) => self.pat_slice(pat),
@@ -29,7 +30,7 @@ impl Printer {
             Pat::Verbatim(pat) => self.pat_verbatim(pat),
             Pat::Wild(pat) => self.pat_wild(pat),
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown Pat"),
+            _ => self.unsupported(UnparseError::unknown("Pat")),
         }
     }
 
@@ -49,6 +50,23 @@ impl Printer {
     }
 
     fn pat_or(&mut self, pat: &PatOr) {
+        self.pat_or_with_leading_vert(pat, false);
+    }
+
+    // Pattern of a match arm, optionally with a `|` ahead of the first case.
+    pub fn arm_pat(&mut self, pat: &Pat, leading_vert: bool) {
+        match pat {
+            Pat::Or(pat) => self.pat_or_with_leading_vert(pat, leading_vert),
+            _ => {
+                if leading_vert {
+                    self.word("| ");
+                }
+                self.pat(pat);
+            }
+        }
+    }
+
+    fn pat_or_with_leading_vert(&mut self, pat: &PatOr, leading_vert: bool) {
         self.outer_attrs(&pat.attrs);
         let mut consistent_break = false;
         for case in &pat.cases {
@@ -65,6 +83,9 @@ impl Printer {
         } else {
             self.ibox(0);
         }
+        if leading_vert {
+            self.word("| ");
+        }
         for case in pat.cases.iter().delimited() {
             if !case.is_first {
                 self.space();
@@ -170,7 +191,7 @@ impl Printer {
 
     #[cfg(not(feature = "verbatim"))]
     fn pat_verbatim(&mut self, pat: &TokenStream) {
-        unimplemented!("Pat::Verbatim `{}`", pat);
+        self.unsupported_verbatim("Pat::Verbatim", pat);
     }
 
     #[cfg(feature = "verbatim")]
@@ -213,7 +234,7 @@ impl Printer {
 
         let pat: PatVerbatim = match syn::parse2(tokens.clone()) {
             Ok(pat) => pat,
-            Err(_) => unimplemented!("Pat::Verbatim `{}`", tokens),
+            Err(_) => return self.unsupported_verbatim("Pat::Verbatim", tokens),
         };
 
         match pat {
@@ -1,6 +1,7 @@
 use crate::algorithm::Printer;
+use crate::fixup::FixupContext;
 use crate::iter::IterDelimited;
-use crate::INDENT;
+use crate::{UnparseError, INDENT};
 use std::ptr;
 use syn::{
     AngleBracketedGenericArguments, AssocConst, AssocType, Constraint, Expr, GenericArgument,
@@ -17,7 +18,7 @@ pub enum PathKind {
     Expr,
 }
 
-impl Printer {
+impl Printer<'_> {
     pub fn path(&mut self, path: &Path, kind: PathKind) {
         assert!(!path.segments.is_empty());
         for segment in path.segments.iter().delimited() {
@@ -57,7 +58,7 @@ impl Printer {
                     // generated code is valid.
                     _ => {
                         self.word("{");
-                        self.expr(expr);
+                        self.expr(expr, FixupContext::new_stmt());
                         self.word("}");
                     }
                 }
@@ -66,7 +67,7 @@ impl Printer {
             GenericArgument::AssocConst(assoc) => self.assoc_const(assoc),
             GenericArgument::Constraint(constraint) => self.constraint(constraint),
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown GenericArgument"),
+            _ => self.unsupported(UnparseError::unknown("GenericArgument")),
         }
     }
 
@@ -135,7 +136,7 @@ impl Printer {
             self.angle_bracketed_generic_arguments(generics, PathKind::Type);
         }
         self.word(" = ");
-        self.expr(&assoc.value);
+        self.expr(&assoc.value, FixupContext::NONE);
     }
 
     fn constraint(&mut self, constraint: &Constraint) {
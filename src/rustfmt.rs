@@ -0,0 +1,566 @@
+use crate::{
+    BraceStyle, ImportsGranularity, MatchArmLeadingPipes, NewlineStyle, Options, SmallHeuristics,
+};
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Names of rustfmt's configuration file, in the order rustfmt looks for them
+// within each directory.
+const FILE_NAMES: [&str; 2] = [".rustfmt.toml", "rustfmt.toml"];
+
+/// Formatting options read from a rustfmt configuration file.
+///
+/// ```no_run
+/// # fn main() -> Result<(), prettyplease::RustfmtConfigError> {
+/// let config = prettyplease::RustfmtConfig::find("src/generated.rs")?;
+/// for key in &config.unsupported {
+///     eprintln!("warning: rustfmt.toml key `{}` is not supported", key);
+/// }
+/// # let syntax_tree = syn::parse_file("").unwrap();
+/// let formatted = prettyplease::unparse_with(&syntax_tree, &config.options);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct RustfmtConfig {
+    /// rustfmt's defaults, with the supported keys of the file applied.
+    ///
+    /// These are [`Options::default()`] except for `max_width`, which is 100
+    /// as in rustfmt rather than prettyplease's 89, and `blank_lines`, which
+    /// is 1 as rustfmt's `blank_lines_upper_bound` rather than 0.
+    ///
+    /// With `use_small_heuristics = "Default"`, the widths that the file does
+    /// not set are derived from `max_width` the way rustfmt derives them:
+    /// `struct_lit_width` is 18% of it, and `fn_call_width`, `chain_width` and
+    /// `array_width` are 60%.
+    pub options: Options,
+
+    /// Location of the file that was read, or None if no file was found.
+    pub path: Option<PathBuf>,
+
+    /// Keys in the file that have no counterpart in [`Options`], or whose
+    /// value is one that prettyplease does not implement, in the order they
+    /// appear. Their effect on formatting is not reproduced.
+    pub unsupported: Vec<String>,
+
+    // Keys that the file sets, whose options are not derived from others.
+    keys: Vec<String>,
+}
+
+/// Error returned when a rustfmt configuration file cannot be read or
+/// contains an invalid value.
+#[derive(Debug)]
+pub struct RustfmtConfigError {
+    path: Option<PathBuf>,
+    line: Option<usize>,
+    message: String,
+    io: Option<io::Error>,
+}
+
+impl RustfmtConfig {
+    /// Looks for `rustfmt.toml` or `.rustfmt.toml` the way rustfmt does, in the
+    /// directory containing `path` and then in each of its ancestors, and reads
+    /// the first one found. The file at `path` does not need to exist yet.
+    ///
+    /// If there is no configuration file, the result has rustfmt's default
+    /// options and a `path` of None.
+    pub fn find(path: impl AsRef<Path>) -> Result<Self, RustfmtConfigError> {
+        let start = start_dir(path.as_ref())?;
+        for dir in start.ancestors() {
+            for name in &FILE_NAMES {
+                let candidate = dir.join(name);
+                if candidate.is_file() {
+                    return RustfmtConfig::load(candidate);
+                }
+            }
+        }
+        Ok(RustfmtConfig::from(rustfmt_defaults()))
+    }
+
+    /// Changes `max_width` as if the file had set it to the given value,
+    /// along with the widths derived from it. This is how rustfmt applies
+    /// `--config max_width=...` on top of a configuration file.
+    ///
+    /// ```
+    /// let mut config = prettyplease::RustfmtConfig::parse("array_width = 50\n").unwrap();
+    /// config.set_max_width(80);
+    /// assert_eq!(config.options.max_width, 80);
+    /// assert_eq!(config.options.fn_call_width, 48);
+    /// assert_eq!(config.options.array_width, 50);
+    /// ```
+    pub fn set_max_width(&mut self, max_width: usize) {
+        self.options.max_width = max_width;
+        derive_widths(&mut self.options, &self.keys);
+    }
+
+    /// Reads the rustfmt configuration file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RustfmtConfigError> {
+        let path = path.as_ref();
+        let contents =
+            fs::read_to_string(path).map_err(|error| RustfmtConfigError::io(path, error))?;
+        let mut config = RustfmtConfig::parse(&contents).map_err(|mut error| {
+            error.path = Some(path.to_owned());
+            error
+        })?;
+        config.path = Some(path.to_owned());
+        Ok(config)
+    }
+
+    /// Parses the contents of a rustfmt configuration file.
+    ///
+    /// ```
+    /// let config = prettyplease::RustfmtConfig::parse(
+    ///     "max_width = 100\nuse_small_heuristics = \"Max\"\nedition = \"2021\"\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(config.options.max_width, 100);
+    /// assert_eq!(config.unsupported, ["edition"]);
+    /// ```
+    pub fn parse(contents: &str) -> Result<Self, RustfmtConfigError> {
+        let mut config = RustfmtConfig::from(rustfmt_defaults());
+        let mut parser = Parser {
+            rest: contents,
+            line: 1,
+        };
+        while let Some((key, line)) = parser.key()? {
+            if config.keys.contains(&key) {
+                return Err(RustfmtConfigError::syntax(
+                    line,
+                    format!("duplicate key `{}`", key),
+                ));
+            }
+            let value = parser.value()?;
+            parser.end_of_line()?;
+            match apply(&mut config.options, &key, &value) {
+                Ok(true) => {}
+                Ok(false) => config.unsupported.push(key.clone()),
+                Err(message) => {
+                    return Err(RustfmtConfigError::syntax(
+                        line,
+                        format!("invalid value for `{}`: {}", key, message),
+                    ));
+                }
+            }
+            config.keys.push(key);
+        }
+        derive_widths(&mut config.options, &config.keys);
+        Ok(config)
+    }
+}
+
+impl From<Options> for RustfmtConfig {
+    fn from(options: Options) -> Self {
+        RustfmtConfig {
+            options,
+            path: None,
+            unsupported: Vec::new(),
+            keys: Vec::new(),
+        }
+    }
+}
+
+// Options that a rustfmt configuration file's settings are applied on top of.
+// Only the defaults that prettyplease has a counterpart for are changed.
+fn rustfmt_defaults() -> Options {
+    let mut options = Options {
+        max_width: 100,
+        #[cfg(feature = "span-locations")]
+        blank_lines: 1,
+        ..Options::default()
+    };
+    derive_widths(&mut options, &[]);
+    options
+}
+
+// Like rustfmt's `use_small_heuristics = "Default"`, set each width that is
+// not in `keys` to a percentage of max_width. The other heuristics do not use
+// these widths.
+fn derive_widths(options: &mut Options, keys: &[String]) {
+    if options.use_small_heuristics != SmallHeuristics::Default {
+        return;
+    }
+    let max_width = options.max_width;
+    for (key, percent, width) in [
+        ("struct_lit_width", 18, &mut options.struct_lit_width),
+        ("fn_call_width", 60, &mut options.fn_call_width),
+        ("chain_width", 60, &mut options.chain_width),
+        ("array_width", 60, &mut options.array_width),
+    ] {
+        if !keys.iter().any(|set| set == key) {
+            *width = max_width * percent / 100;
+        }
+    }
+}
+
+// Directory to start searching for a configuration file from: the directory
+// `path` names, or else the one containing it. A path that does not exist is
+// resolved from its nearest existing ancestor.
+fn start_dir(path: &Path) -> Result<PathBuf, RustfmtConfigError> {
+    let mut missing = path;
+    loop {
+        match fs::canonicalize(missing) {
+            Ok(canonical) => {
+                return Ok(if missing == path && !canonical.is_dir() {
+                    canonical.parent().map_or(canonical.clone(), Path::to_owned)
+                } else {
+                    canonical
+                });
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound && missing != Path::new(".") => {
+                missing = match missing.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent,
+                    _ => Path::new("."),
+                };
+            }
+            Err(error) => return Err(RustfmtConfigError::io(missing, error)),
+        }
+    }
+}
+
+// Sets the option corresponding to a rustfmt configuration key. Returns
+// Ok(false) if prettyplease has nothing corresponding to the key or value.
+fn apply(options: &mut Options, key: &str, value: &Value) -> Result<bool, String> {
+    match key {
+        "max_width" => options.max_width = value.usize()?,
+        "tab_spaces" => options.tab_spaces = value.usize()?,
+        "hard_tabs" => options.hard_tabs = value.bool()?,
+        "newline_style" => {
+            options.newline_style = match value.str()? {
+                "Unix" => NewlineStyle::Unix,
+                "Windows" => NewlineStyle::Windows,
+                "Native" => NewlineStyle::Native,
+                "Auto" => return Ok(false),
+                other => return Err(unknown_variant(other)),
+            };
+        }
+        "use_small_heuristics" => {
+            options.use_small_heuristics = match value.str()? {
+                "Default" => SmallHeuristics::Default,
+                "Off" => SmallHeuristics::Off,
+                "Max" => SmallHeuristics::Max,
+                other => return Err(unknown_variant(other)),
+            };
+        }
+        "struct_lit_width" => options.struct_lit_width = value.usize()?,
+        "fn_call_width" => options.fn_call_width = value.usize()?,
+        "chain_width" => options.chain_width = value.usize()?,
+        "array_width" => options.array_width = value.usize()?,
+        #[cfg(feature = "span-locations")]
+        "blank_lines_upper_bound" => options.blank_lines = value.usize()?,
+        "reorder_imports" => options.reorder_imports = value.bool()?,
+        "imports_granularity" => {
+            options.imports_granularity = match value.str()? {
+                "Preserve" => ImportsGranularity::Preserve,
+                "Crate" => ImportsGranularity::Crate,
+                "Module" => ImportsGranularity::Module,
+                "Item" => ImportsGranularity::Item,
+                "One" => ImportsGranularity::One,
+                other => return Err(unknown_variant(other)),
+            };
+        }
+        "brace_style" => {
+            options.brace_style = match value.str()? {
+                "SameLineWhere" => BraceStyle::SameLineWhere,
+                "AlwaysNextLine" => BraceStyle::AlwaysNextLine,
+                "PreferSameLine" => return Ok(false),
+                other => return Err(unknown_variant(other)),
+            };
+        }
+        "where_single_line" => options.where_single_line = value.bool()?,
+        "match_arm_blocks" => options.match_arm_blocks = value.bool()?,
+        "match_arm_leading_pipes" => {
+            options.match_arm_leading_pipes = match value.str()? {
+                "Never" => MatchArmLeadingPipes::Never,
+                "Always" => MatchArmLeadingPipes::Always,
+                "Preserve" => MatchArmLeadingPipes::Preserve,
+                other => return Err(unknown_variant(other)),
+            };
+        }
+        "match_block_trailing_comma" => options.match_block_trailing_comma = value.bool()?,
+        "normalize_doc_attributes" => options.normalize_doc_attributes = value.bool()?,
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+fn unknown_variant(variant: &str) -> String {
+    format!("unknown variant `{}`", variant)
+}
+
+enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+    Array,
+}
+
+impl Value {
+    fn usize(&self) -> Result<usize, String> {
+        match self {
+            Value::Integer(int) => usize::try_from(*int)
+                .map_err(|_| format!("expected a non-negative integer, found {}", int)),
+            _ => Err(self.unexpected("an integer")),
+        }
+    }
+
+    fn bool(&self) -> Result<bool, String> {
+        match self {
+            Value::Boolean(b) => Ok(*b),
+            _ => Err(self.unexpected("true or false")),
+        }
+    }
+
+    fn str(&self) -> Result<&str, String> {
+        match self {
+            Value::String(string) => Ok(string),
+            _ => Err(self.unexpected("a string")),
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> String {
+        let found = match self {
+            Value::String(_) => "a string",
+            Value::Integer(_) => "an integer",
+            Value::Boolean(_) => "a boolean",
+            Value::Array => "an array",
+        };
+        format!("expected {}, found {}", expected, found)
+    }
+}
+
+// Parser for the subset of TOML used by rustfmt configuration files: top-level
+// keys whose values are strings, integers, booleans, or arrays of those.
+struct Parser<'a> {
+    rest: &'a str,
+    line: usize,
+}
+
+impl Parser<'_> {
+    // Next key and the line it is on, or None at the end of the input.
+    fn key(&mut self) -> Result<Option<(String, usize)>, RustfmtConfigError> {
+        self.skip_blank_lines();
+        let line = self.line;
+        let key = match self.peek() {
+            None => return Ok(None),
+            Some('"' | '\'') => self.string()?,
+            Some('[') => return Err(self.error("tables are not supported")),
+            Some(_) => {
+                let len = self
+                    .rest
+                    .find(|ch: char| !is_bare_key_char(ch))
+                    .unwrap_or(self.rest.len());
+                if len == 0 {
+                    return Err(self.error("expected a key"));
+                }
+                let key = self.rest[..len].to_owned();
+                self.rest = &self.rest[len..];
+                key
+            }
+        };
+        self.skip_spaces();
+        match self.peek() {
+            Some('=') => self.rest = &self.rest[1..],
+            Some('.') => return Err(self.error("dotted keys are not supported")),
+            _ => return Err(self.error("expected `=` after key")),
+        }
+        self.skip_spaces();
+        Ok(Some((key, line)))
+    }
+
+    fn value(&mut self) -> Result<Value, RustfmtConfigError> {
+        match self.peek() {
+            Some('"' | '\'') => self.string().map(Value::String),
+            Some('[') => {
+                self.rest = &self.rest[1..];
+                loop {
+                    self.skip_blank_lines();
+                    if self.peek() == Some(']') {
+                        break;
+                    }
+                    self.value()?;
+                    self.skip_blank_lines();
+                    match self.peek() {
+                        Some(',') => self.rest = &self.rest[1..],
+                        Some(']') => break,
+                        _ => return Err(self.error("expected `,` or `]` in array")),
+                    }
+                }
+                self.rest = &self.rest[1..];
+                Ok(Value::Array)
+            }
+            Some('{') => Err(self.error("inline tables are not supported")),
+            Some(_) => {
+                let len = self
+                    .rest
+                    .find(|ch: char| !is_bare_key_char(ch) && ch != '+' && ch != '.')
+                    .unwrap_or(self.rest.len());
+                let word = &self.rest[..len];
+                let value = match word {
+                    "true" => Value::Boolean(true),
+                    "false" => Value::Boolean(false),
+                    _ => match word.replace('_', "").parse() {
+                        Ok(int) => Value::Integer(int),
+                        Err(_) if word.is_empty() => return Err(self.error("expected a value")),
+                        Err(_) => {
+                            return Err(self.error(format!("unsupported value `{}`", word)));
+                        }
+                    },
+                };
+                self.rest = &self.rest[len..];
+                Ok(value)
+            }
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, RustfmtConfigError> {
+        if self.rest.starts_with("\"\"\"") || self.rest.starts_with("'''") {
+            return Err(self.error("multi-line strings are not supported"));
+        }
+        let quote = self.rest.chars().next().unwrap();
+        let mut string = String::new();
+        let mut chars = self.rest[1..].char_indices();
+        while let Some((i, ch)) = chars.next() {
+            match ch {
+                '\n' => break,
+                _ if ch == quote => {
+                    self.rest = &self.rest[1 + i + 1..];
+                    return Ok(string);
+                }
+                '\\' if quote == '"' => {
+                    let escaped = match chars.next() {
+                        Some((_, 'b')) => '\u{8}',
+                        Some((_, 't')) => '\t',
+                        Some((_, 'n')) => '\n',
+                        Some((_, 'f')) => '\u{c}',
+                        Some((_, 'r')) => '\r',
+                        Some((_, '"')) => '"',
+                        Some((_, '\\')) => '\\',
+                        Some((_, 'u')) => self.unicode_escape(&mut chars, 4)?,
+                        Some((_, 'U')) => self.unicode_escape(&mut chars, 8)?,
+                        _ => return Err(self.error("invalid escape in string")),
+                    };
+                    string.push(escaped);
+                }
+                _ => string.push(ch),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn unicode_escape(
+        &self,
+        chars: &mut std::str::CharIndices,
+        digits: usize,
+    ) -> Result<char, RustfmtConfigError> {
+        let mut code = 0;
+        for _ in 0..digits {
+            match chars.next().and_then(|(_, ch)| ch.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return Err(self.error("invalid unicode escape in string")),
+            }
+        }
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape in string"))
+    }
+
+    fn end_of_line(&mut self) -> Result<(), RustfmtConfigError> {
+        self.skip_spaces();
+        match self.peek() {
+            None | Some('\n' | '\r' | '#') => Ok(()),
+            Some(_) => Err(self.error("expected newline after value")),
+        }
+    }
+
+    // Skips whitespace, newlines and comments.
+    fn skip_blank_lines(&mut self) {
+        loop {
+            self.skip_spaces();
+            if self.rest.starts_with('#') {
+                let len = self.rest.find('\n').unwrap_or(self.rest.len());
+                self.rest = &self.rest[len..];
+            }
+            if self.rest.starts_with('\n') {
+                self.rest = &self.rest[1..];
+                self.line += 1;
+            } else if self.rest.starts_with("\r\n") {
+                self.rest = &self.rest[2..];
+                self.line += 1;
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        self.rest = self.rest.trim_start_matches(|ch| ch == ' ' || ch == '\t');
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest.chars().next()
+    }
+
+    fn error(&self, message: impl Into<String>) -> RustfmtConfigError {
+        RustfmtConfigError::syntax(self.line, message.into())
+    }
+}
+
+fn is_bare_key_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_' || ch == '-'
+}
+
+impl RustfmtConfigError {
+    fn io(path: &Path, error: io::Error) -> Self {
+        RustfmtConfigError {
+            path: Some(path.to_owned()),
+            line: None,
+            message: error.to_string(),
+            io: Some(error),
+        }
+    }
+
+    fn syntax(line: usize, message: String) -> Self {
+        RustfmtConfigError {
+            path: None,
+            line: Some(line),
+            message,
+            io: None,
+        }
+    }
+
+    /// The configuration file that the error occurred in, if it was read from
+    /// a file.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// The line of the configuration file that the error occurred on, if the
+    /// error is about its contents.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+}
+
+impl Display for RustfmtConfigError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.path, self.line) {
+            (Some(path), Some(line)) => write!(formatter, "{}:{}: ", path.display(), line)?,
+            (Some(path), None) => write!(formatter, "{}: ", path.display())?,
+            (None, Some(line)) => write!(formatter, "line {}: ", line)?,
+            (None, None) => {}
+        }
+        formatter.write_str(&self.message)
+    }
+}
+
+impl Error for RustfmtConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.io {
+            Some(error) => Some(error),
+            None => None,
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use proc_macro2::Span;
+
+/// Correspondence between positions in formatted output and the spans of the
+/// syntax tree tokens printed there, as produced by
+/// [`unparse_with_source_map`][crate::unparse_with_source_map].
+///
+/// The tokens that are recorded are:
+///
+/// - identifiers, including path segments such as `self`, `Self`, `crate` and
+///   `super`, and identifiers within macro invocations;
+/// - lifetimes, as two mappings for the `'` and the name;
+/// - literals, including `true` and `false`;
+/// - tuple field indices, such as the `0` in `x.0`.
+///
+/// Keywords, punctuation and delimiters are printed from the syntax tree's
+/// structure rather than from its tokens, so they have no mapping of their
+/// own. This includes punctuation within macro invocations.
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    mappings: Vec<Mapping>,
+    // Progress of line counting through the output, as a byte offset
+    counted: usize,
+    line: usize,
+    line_start: usize,
+}
+
+/// One token of formatted output and the span it was printed from.
+///
+/// Lines and columns follow the same convention as
+/// `proc_macro2::LineColumn`: lines are 1-based and columns are 0-based,
+/// counted in chars.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Mapping {
+    /// Span of the syntax tree token.
+    pub span: Span,
+    /// Byte offset of the token in the formatted output.
+    pub offset: usize,
+    /// Length of the token in bytes.
+    pub len: usize,
+    /// Line of the formatted output on which the token starts.
+    pub line: usize,
+    /// Column of the formatted output at which the token starts.
+    pub column: usize,
+}
+
+impl SourceMap {
+    pub(crate) fn new() -> Self {
+        SourceMap {
+            line: 1,
+            ..SourceMap::default()
+        }
+    }
+
+    /// All mappings, in order of their position in the formatted output.
+    pub fn mappings(&self) -> &[Mapping] {
+        &self.mappings
+    }
+
+    /// The mapping for the token at the given line and column of formatted
+    /// output, or for the closest token before it if that position is not
+    /// within an identifier, lifetime or literal.
+    ///
+    /// ```
+    /// let syntax_tree: syn::File = syn::parse_str("fn main() { let x = 1; }").unwrap();
+    /// let options = prettyplease::Options::default();
+    /// let (formatted, source_map) = prettyplease::unparse_with_source_map(&syntax_tree, &options);
+    /// assert_eq!(formatted, "fn main() {\n    let x = 1;\n}\n");
+    ///
+    /// let mapping = source_map.lookup(2, 9).unwrap();
+    /// assert_eq!(&formatted[mapping.offset..][..mapping.len], "x");
+    /// ```
+    pub fn lookup(&self, line: usize, column: usize) -> Option<&Mapping> {
+        let index = self
+            .mappings
+            .partition_point(|mapping| (mapping.line, mapping.column) <= (line, column));
+        index.checked_sub(1).map(|index| &self.mappings[index])
+    }
+
+    // Record that `len` bytes printed from `span` are about to be appended to
+    // `out`, whose first byte is at offset `base` in the overall output.
+    pub(crate) fn record(&mut self, out: &str, base: usize, span: Span, len: usize) {
+        self.count_lines(out, base);
+        let offset = base + out.len();
+        let column = out[self.line_start.saturating_sub(base)..].chars().count();
+        self.mappings.push(Mapping {
+            span,
+            offset,
+            len,
+            line: self.line,
+            column,
+        });
+    }
+
+    // Must be called before output is discarded from `out`.
+    pub(crate) fn count_lines(&mut self, out: &str, base: usize) {
+        let rest = &out[self.counted - base..];
+        for (i, ch) in rest.char_indices() {
+            if ch == '\n' {
+                self.line += 1;
+                self.line_start = self.counted + i + 1;
+            }
+        }
+        self.counted = base + out.len();
+    }
+}
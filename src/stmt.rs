@@ -1,8 +1,10 @@
 use crate::algorithm::Printer;
+use crate::classify;
+use crate::fixup::FixupContext;
 use crate::INDENT;
 use syn::{BinOp, Expr, Stmt};
 
-impl Printer {
+impl Printer<'_> {
     pub fn stmt(&mut self, stmt: &Stmt) {
         match stmt {
             Stmt::Local(local) => {
@@ -13,7 +15,13 @@ impl Printer {
                 if let Some(local_init) = &local.init {
                     self.word(" = ");
                     self.neverbreak();
-                    self.expr(&local_init.expr);
+                    self.subexpr(
+                        &local_init.expr,
+                        local_init.diverge.is_some()
+                            && (classify::expr_trailing_brace(&local_init.expr)
+                                || classify::expr_is_lazy_boolean(&local_init.expr)),
+                        FixupContext::NONE,
+                    );
                     if let Some((_else, diverge)) = &local_init.diverge {
                         self.word(" else ");
                         if let Expr::Block(expr) = diverge.as_ref() {
@@ -22,7 +30,7 @@ impl Printer {
                             self.word("{");
                             self.space();
                             self.ibox(INDENT);
-                            self.expr(diverge);
+                            self.expr(diverge, FixupContext::new_stmt());
                             self.end();
                             self.space();
                             self.offset(-INDENT);
@@ -38,14 +46,14 @@ impl Printer {
             Stmt::Expr(expr, None) => {
                 if break_after(expr) {
                     self.ibox(0);
-                    self.expr_beginning_of_line(expr, true);
+                    self.expr_beginning_of_line(expr, true, FixupContext::new_stmt());
                     if add_semi(expr) {
                         self.word(";");
                     }
                     self.end();
                     self.hardbreak();
                 } else {
-                    self.expr_beginning_of_line(expr, true);
+                    self.expr_beginning_of_line(expr, true, FixupContext::new_stmt());
                 }
             }
             Stmt::Expr(expr, Some(_semi)) => {
@@ -55,7 +63,7 @@ impl Printer {
                     }
                 }
                 self.ibox(0);
-                self.expr_beginning_of_line(expr, true);
+                self.expr_beginning_of_line(expr, true, FixupContext::new_stmt());
                 if !remove_semi(expr) {
                     self.word(";");
                 }
@@ -107,7 +115,7 @@ pub fn add_semi(expr: &Expr) -> bool {
             | BinOp::Ge(_)
             | BinOp::Gt(_) => false,
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown BinOp"),
+            _ => false,
         },
         Expr::Group(group) => add_semi(&group.expr),
 
@@ -158,7 +166,7 @@ pub fn break_after(expr: &Expr) -> bool {
     true
 }
 
-fn remove_semi(expr: &Expr) -> bool {
+pub fn remove_semi(expr: &Expr) -> bool {
     match expr {
         Expr::ForLoop(_) | Expr::While(_) => true,
         Expr::Group(group) => remove_semi(&group.expr),
@@ -1,7 +1,7 @@
 use crate::algorithm::Printer;
 use proc_macro2::{Delimiter, Ident, Literal, Spacing, TokenStream, TokenTree};
 
-impl Printer {
+impl Printer<'_> {
     pub fn single_token(&mut self, token: Token, group_contents: fn(&mut Self, TokenStream)) {
         match token {
             Token::Group(delimiter, stream) => self.token_group(delimiter, stream, group_contents),
@@ -31,7 +31,7 @@ impl Printer {
     }
 
     pub fn ident(&mut self, ident: &Ident) {
-        self.word(ident.to_string());
+        self.word_spanned(ident.to_string(), ident.span());
     }
 
     pub fn token_punct(&mut self, ch: char) {
@@ -39,7 +39,7 @@ impl Printer {
     }
 
     pub fn token_literal(&mut self, literal: &Literal) {
-        self.word(literal.to_string());
+        self.word_spanned(literal.to_string(), literal.span());
     }
 
     pub fn delimiter_open(&mut self, delimiter: Delimiter) {
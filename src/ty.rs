@@ -1,7 +1,8 @@
 use crate::algorithm::Printer;
+use crate::fixup::FixupContext;
 use crate::iter::IterDelimited;
 use crate::path::PathKind;
-use crate::INDENT;
+use crate::{UnparseError, INDENT};
 use proc_macro2::TokenStream;
 use syn::{
     Abi, BareFnArg, BareVariadic, ReturnType, Type, TypeArray, TypeBareFn, TypeGroup,
@@ -9,7 +10,7 @@ use syn::{
     TypeSlice, TypeTraitObject, TypeTuple,
 };
 
-impl Printer {
+impl Printer<'_> {
     pub fn ty(&mut self, ty: &Type) {
         match ty {
             Type::Array(ty) => self.type_array(ty),
@@ -28,7 +29,7 @@ impl Printer {
             Type::Tuple(ty) => self.type_tuple(ty),
             Type::Verbatim(ty) => self.type_verbatim(ty),
             #[cfg_attr(all(test, exhaustive), deny(non_exhaustive_omitted_patterns))]
-            _ => unimplemented!("unknown Type"),
+            _ => self.unsupported(UnparseError::unknown("Type")),
         }
     }
 
@@ -36,7 +37,7 @@ impl Printer {
         self.word("[");
         self.ty(&ty.elem);
         self.word("; ");
-        self.expr(&ty.len);
+        self.expr(&ty.len, FixupContext::NONE);
         self.word("]");
     }
 
@@ -163,7 +164,7 @@ impl Printer {
 
     #[cfg(not(feature = "verbatim"))]
     fn type_verbatim(&mut self, ty: &TokenStream) {
-        unimplemented!("Type::Verbatim `{}`", ty);
+        self.unsupported_verbatim("Type::Verbatim", ty);
     }
 
     #[cfg(feature = "verbatim")]
@@ -221,7 +222,7 @@ impl Printer {
 
         let ty: TypeVerbatim = match syn::parse2(tokens.clone()) {
             Ok(ty) => ty,
-            Err(_) => unimplemented!("Type::Verbatim `{}`", tokens),
+            Err(_) => return self.unsupported_verbatim("Type::Verbatim", tokens),
         };
 
         match ty {
@@ -0,0 +1,91 @@
+use crate::Options;
+use proc_macro2::TokenStream;
+use std::borrow::Cow;
+use std::mem;
+use syn::visit_mut::{self, VisitMut};
+use syn::{Expr, File, Pat, PatReference, Type};
+
+pub fn file<'a>(file: &'a File, options: &Options) -> Cow<'a, File> {
+    if options.remove_redundant_parens {
+        let mut file = file.clone();
+        remove_redundant_parens(&mut file);
+        Cow::Owned(file)
+    } else {
+        Cow::Borrowed(file)
+    }
+}
+
+// Strip the parentheses in the syntax tree that are not needed to preserve its
+// structure. Parentheses in expressions are all removed, because the printer
+// inserts the ones required by operator precedence on its own. Types and
+// patterns are printed as they are, so only the parentheses that are provably
+// redundant are removed from those.
+pub fn remove_redundant_parens(file: &mut File) {
+    let mut unparen = Unparen {
+        after_reference: false,
+    };
+    unparen.visit_file_mut(file);
+}
+
+struct Unparen {
+    // Whether the pattern being visited is the operand of a `&` pattern.
+    after_reference: bool,
+}
+
+impl VisitMut for Unparen {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        visit_mut::visit_expr_mut(self, expr);
+        while let Expr::Paren(paren) = expr {
+            if !paren.attrs.is_empty() {
+                break;
+            }
+            let inner = mem::replace(&mut *paren.expr, Expr::Verbatim(TokenStream::new()));
+            *expr = inner;
+        }
+    }
+
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        visit_mut::visit_type_mut(self, ty);
+        while let Type::Paren(paren) = ty {
+            // `&(dyn Trait + Send)` and `fn() -> (impl Trait + Send)` are not
+            // the same without parentheses, if they parse at all.
+            let multiple_bounds = match &*paren.elem {
+                Type::ImplTrait(inner) => inner.bounds.len() > 1,
+                Type::TraitObject(inner) => inner.bounds.len() > 1,
+                _ => false,
+            };
+            if multiple_bounds {
+                break;
+            }
+            let inner = mem::replace(&mut *paren.elem, Type::Verbatim(TokenStream::new()));
+            *ty = inner;
+        }
+    }
+
+    fn visit_pat_mut(&mut self, pat: &mut Pat) {
+        let after_reference = mem::replace(&mut self.after_reference, false);
+        visit_mut::visit_pat_mut(self, pat);
+        while let Pat::Paren(paren) = pat {
+            match &*paren.pat {
+                // Or-patterns are not allowed at the top level of closure and
+                // function parameters, and a range pattern is ambiguous after
+                // `&` or in a slice pattern.
+                Pat::Or(_) | Pat::Range(_) => break,
+                Pat::Ident(inner) if after_reference && inner.mutability.is_some() => break,
+                _ => {}
+            }
+            let inner = mem::replace(&mut *paren.pat, Pat::Verbatim(TokenStream::new()));
+            *pat = inner;
+        }
+    }
+
+    fn visit_pat_reference_mut(&mut self, pat: &mut PatReference) {
+        for attr in &mut pat.attrs {
+            self.visit_attribute_mut(attr);
+        }
+        // `&(mut x)` matches through a shared reference and binds `x` mutably,
+        // whereas `&mut x` matches through a mutable reference.
+        self.after_reference = true;
+        self.visit_pat_mut(&mut pat.pat);
+    }
+}
@@ -0,0 +1,625 @@
+#[cfg(feature = "unparen")]
+use crate::unparen;
+use crate::{stmt, ImportsGranularity, Options, UnparseError};
+use proc_macro2::{Delimiter, Group, Ident, Punct, Spacing, TokenStream, TokenTree};
+use quote::ToTokens;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::mem;
+use syn::visit_mut::{self, VisitMut};
+#[cfg(feature = "unparen")]
+use syn::ExprParen;
+use syn::{
+    token, AngleBracketedGenericArguments, Arm, BoundLifetimes, Block, Expr, ExprClosure,
+    ExprMacro, FieldsNamed, FieldsUnnamed, File, Generics, Item, ItemEnum, ItemMacro, ItemMod,
+    ItemUse, Lit, MacroDelimiter, MetaList, ParenthesizedGenericArguments, Pat, ReturnType,
+    Signature, Stmt, Type, UseGroup, UseName, UsePath, UseRename, UseTree,
+};
+
+/// Error returned by [`unparse_verified`][crate::unparse_verified].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum VerifyError {
+    /// The syntax tree contains a node that prettyplease does not know how to
+    /// print.
+    Unsupported(UnparseError),
+    /// The formatted output is not valid Rust syntax.
+    Reparse(syn::Error),
+    /// The formatted output parses, but not to the same tokens as the input.
+    /// Holds the first differing token of the input and of the output, or
+    /// None where one of them ended early.
+    TokensDiffer(Option<String>, Option<String>),
+    /// Formatting the formatted output again changes it.
+    NotIdempotent,
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VerifyError::Unsupported(error) => write!(formatter, "unable to format {}", error),
+            VerifyError::Reparse(error) => {
+                write!(formatter, "formatted output failed to parse: {}", error)
+            }
+            VerifyError::TokensDiffer(expected, found) => {
+                formatter.write_str("formatted output has different tokens than the input: ")?;
+                match (expected, found) {
+                    (Some(expected), Some(found)) => {
+                        write!(formatter, "expected `{}`, found `{}`", expected, found)
+                    }
+                    (Some(expected), None) => write!(formatter, "missing `{}`", expected),
+                    (None, Some(found)) => write!(formatter, "unexpected `{}`", found),
+                    (None, None) => unreachable!(),
+                }
+            }
+            VerifyError::NotIdempotent => {
+                formatter.write_str("formatting the formatted output again changes it")
+            }
+        }
+    }
+}
+
+impl Error for VerifyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            VerifyError::Unsupported(error) => Some(error),
+            VerifyError::Reparse(error) => Some(error),
+            VerifyError::TokensDiffer(..) | VerifyError::NotIdempotent => None,
+        }
+    }
+}
+
+/// Like [`try_unparse_with`][crate::try_unparse_with], but checks the
+/// formatted output before returning it.
+///
+/// The output is parsed again and must consist of the same tokens as the input
+/// syntax tree, disregarding spans and insignificant differences such as
+/// trailing commas. Formatting the reparsed output must also reproduce it
+/// exactly.
+///
+/// Requires the `"verify"` feature.
+///
+/// ```
+/// let syntax_tree: syn::File = syn::parse_str("fn main() { let x = (1, 2,); }").unwrap();
+/// let options = prettyplease::Options::default();
+/// let formatted = prettyplease::unparse_verified(&syntax_tree, &options).unwrap();
+/// assert_eq!(formatted, "fn main() {\n    let x = (1, 2);\n}\n");
+/// ```
+pub fn unparse_verified(file: &File, options: &Options) -> Result<String, VerifyError> {
+    let formatted = crate::try_unparse_with(file, options).map_err(VerifyError::Unsupported)?;
+    let reparsed = syn::parse_file(&formatted).map_err(VerifyError::Reparse)?;
+
+    let mut expected = Vec::new();
+    flatten(
+        normalize(file.clone(), options).to_token_stream(),
+        &mut expected,
+    );
+    let mut found = Vec::new();
+    flatten(
+        normalize(reparsed.clone(), options).to_token_stream(),
+        &mut found,
+    );
+    if options.newline_style.as_str() != "\n" {
+        crlf_literals(&mut expected);
+        crlf_literals(&mut found);
+    }
+    if options.wrap_doc_comments || options.normalize_doc_attributes {
+        join_docs(&mut expected);
+        join_docs(&mut found);
+    }
+    if let Some(i) = (0..expected.len().max(found.len())).find(|&i| expected.get(i) != found.get(i))
+    {
+        let expected = expected.get(i).map(ToString::to_string);
+        let found = found.get(i).map(ToString::to_string);
+        return Err(VerifyError::TokensDiffer(expected, found));
+    }
+
+    let reformatted =
+        crate::try_unparse_with(&reparsed, options).map_err(VerifyError::Unsupported)?;
+    if reformatted != formatted {
+        return Err(VerifyError::NotIdempotent);
+    }
+
+    Ok(formatted)
+}
+
+// Undo the changes that prettyplease makes to the syntax tree on purpose.
+fn normalize(mut file: File, options: &Options) -> File {
+    struct Normalize {
+        imports: bool,
+    }
+
+    impl VisitMut for Normalize {
+        fn visit_file_mut(&mut self, file: &mut File) {
+            if self.imports {
+                normalize_uses(&mut file.items);
+            }
+            visit_mut::visit_file_mut(self, file);
+        }
+
+        fn visit_item_mod_mut(&mut self, item: &mut ItemMod) {
+            if self.imports {
+                if let Some((_brace, items)) = &mut item.content {
+                    normalize_uses(items);
+                }
+            }
+            visit_mut::visit_item_mod_mut(self, item);
+        }
+
+        // Trailing commas are printed in lists that are broken onto multiple
+        // lines and left out of the ones that fit on one. Only the lists in
+        // which a trailing comma makes no difference are normalized, unlike a
+        // tuple of one element.
+        fn visit_expr_mut(&mut self, expr: &mut Expr) {
+            match expr {
+                Expr::Array(expr) => {
+                    expr.elems.pop_punct();
+                }
+                Expr::Call(expr) => {
+                    expr.args.pop_punct();
+                }
+                Expr::MethodCall(expr) => {
+                    expr.args.pop_punct();
+                }
+                Expr::Struct(expr) if expr.rest.is_none() => {
+                    expr.fields.pop_punct();
+                }
+                Expr::Tuple(expr) if expr.elems.len() > 1 => {
+                    expr.elems.pop_punct();
+                }
+                _ => {}
+            }
+            visit_mut::visit_expr_mut(self, expr);
+        }
+
+        fn visit_pat_mut(&mut self, pat: &mut Pat) {
+            match pat {
+                Pat::Slice(pat) => {
+                    pat.elems.pop_punct();
+                }
+                Pat::Struct(pat) if pat.rest.is_none() => {
+                    pat.fields.pop_punct();
+                }
+                Pat::Tuple(pat) if pat.elems.len() > 1 => {
+                    pat.elems.pop_punct();
+                }
+                Pat::TupleStruct(pat) => {
+                    pat.elems.pop_punct();
+                }
+                _ => {}
+            }
+            visit_mut::visit_pat_mut(self, pat);
+        }
+
+        fn visit_type_mut(&mut self, ty: &mut Type) {
+            match ty {
+                Type::BareFn(ty) if ty.variadic.is_none() => {
+                    ty.inputs.pop_punct();
+                }
+                Type::Tuple(ty) if ty.elems.len() > 1 => {
+                    ty.elems.pop_punct();
+                }
+                _ => {}
+            }
+            visit_mut::visit_type_mut(self, ty);
+        }
+
+        fn visit_generics_mut(&mut self, generics: &mut Generics) {
+            generics.params.pop_punct();
+            if let Some(where_clause) = &mut generics.where_clause {
+                where_clause.predicates.pop_punct();
+            }
+            visit_mut::visit_generics_mut(self, generics);
+        }
+
+        fn visit_bound_lifetimes_mut(&mut self, bound: &mut BoundLifetimes) {
+            bound.lifetimes.pop_punct();
+            visit_mut::visit_bound_lifetimes_mut(self, bound);
+        }
+
+        fn visit_angle_bracketed_generic_arguments_mut(
+            &mut self,
+            arguments: &mut AngleBracketedGenericArguments,
+        ) {
+            arguments.args.pop_punct();
+            visit_mut::visit_angle_bracketed_generic_arguments_mut(self, arguments);
+        }
+
+        fn visit_parenthesized_generic_arguments_mut(
+            &mut self,
+            arguments: &mut ParenthesizedGenericArguments,
+        ) {
+            arguments.inputs.pop_punct();
+            visit_mut::visit_parenthesized_generic_arguments_mut(self, arguments);
+        }
+
+        fn visit_signature_mut(&mut self, sig: &mut Signature) {
+            if sig.variadic.is_none() {
+                sig.inputs.pop_punct();
+            }
+            visit_mut::visit_signature_mut(self, sig);
+        }
+
+        fn visit_fields_named_mut(&mut self, fields: &mut FieldsNamed) {
+            fields.named.pop_punct();
+            visit_mut::visit_fields_named_mut(self, fields);
+        }
+
+        fn visit_fields_unnamed_mut(&mut self, fields: &mut FieldsUnnamed) {
+            fields.unnamed.pop_punct();
+            visit_mut::visit_fields_unnamed_mut(self, fields);
+        }
+
+        fn visit_item_enum_mut(&mut self, item: &mut ItemEnum) {
+            item.variants.pop_punct();
+            visit_mut::visit_item_enum_mut(self, item);
+        }
+
+        fn visit_use_group_mut(&mut self, group: &mut UseGroup) {
+            group.items.pop_punct();
+            visit_mut::visit_use_group_mut(self, group);
+        }
+
+        // The list of traits in a derive attribute gets a trailing comma when
+        // it is broken onto multiple lines.
+        fn visit_meta_list_mut(&mut self, meta: &mut MetaList) {
+            let derive = meta.path.is_ident("derive");
+            meta.tokens = normalize_derives(mem::take(&mut meta.tokens), derive);
+            visit_mut::visit_meta_list_mut(self, meta);
+        }
+
+        // Match arm bodies and closure bodies are printed with or without
+        // braces depending on whether they fit on one line, and the comma
+        // after an arm depends on its body and on match_block_trailing_comma.
+        fn visit_arm_mut(&mut self, arm: &mut Arm) {
+            arm.comma = None;
+            unwrap_body(&mut arm.body);
+            match &*arm.body {
+                Expr::Tuple(expr) if expr.attrs.is_empty() && expr.elems.is_empty() => {
+                    *arm.body = Expr::Verbatim(TokenStream::new());
+                }
+                Expr::Block(expr)
+                    if expr.attrs.is_empty()
+                        && expr.label.is_none()
+                        && expr.block.stmts.is_empty() =>
+                {
+                    *arm.body = Expr::Verbatim(TokenStream::new());
+                }
+                _ => {}
+            }
+            // The leading `|` of the pattern depends on match_arm_leading_pipes.
+            if let Pat::Or(pat) = &mut arm.pat {
+                pat.leading_vert = None;
+                if pat.attrs.is_empty() && pat.cases.len() == 1 {
+                    arm.pat = pat.cases.pop().unwrap().into_value();
+                }
+            }
+            visit_mut::visit_arm_mut(self, arm);
+        }
+
+        fn visit_expr_closure_mut(&mut self, closure: &mut ExprClosure) {
+            if let ReturnType::Default = closure.output {
+                unwrap_body(&mut closure.body);
+                // A body that cannot be printed as the first statement of a
+                // block is wrapped in parentheses instead.
+                if let Expr::Paren(paren) = &mut *closure.body {
+                    if paren.attrs.is_empty() {
+                        let inner =
+                            mem::replace(&mut *paren.expr, Expr::Verbatim(TokenStream::new()));
+                        *closure.body = inner;
+                    }
+                }
+            }
+            closure.inputs.pop_punct();
+            visit_mut::visit_expr_closure_mut(self, closure);
+        }
+
+        // Semicolons are added after assignments and control flow at the end
+        // of a block, and removed after loops and `if` statements.
+        fn visit_block_mut(&mut self, block: &mut Block) {
+            for stmt in &mut block.stmts {
+                if let Stmt::Expr(expr, semi) = stmt {
+                    if semi.is_none() && stmt::add_semi(expr) {
+                        *semi = Some(token::Semi::default());
+                    } else if semi.is_some() && stmt::remove_semi(expr) {
+                        *semi = None;
+                    }
+                }
+            }
+            visit_mut::visit_block_mut(self, block);
+        }
+
+        // Rules of macro_rules are always printed in braces.
+        fn visit_item_macro_mut(&mut self, item: &mut ItemMacro) {
+            if item.ident.is_some() && item.mac.path.is_ident("macro_rules") {
+                item.mac.delimiter = MacroDelimiter::Brace(token::Brace::default());
+                item.semi_token = None;
+                let mut rules = Vec::new();
+                let mut expander = false;
+                for mut tt in item.mac.tokens.clone() {
+                    if let TokenTree::Group(group) = &tt {
+                        if expander {
+                            tt = TokenTree::Group(Group::new(Delimiter::Brace, group.stream()));
+                        }
+                    }
+                    expander = matches!(&tt, TokenTree::Punct(punct) if punct.as_char() == '>');
+                    rules.push(tt);
+                }
+                if let Some(TokenTree::Group(_)) = rules.last() {
+                    rules.push(TokenTree::Punct(Punct::new(';', Spacing::Alone)));
+                }
+                item.mac.tokens = rules.into_iter().collect();
+            }
+            visit_mut::visit_item_macro_mut(self, item);
+        }
+    }
+
+    // With remove_redundant_parens, whether the output has parentheses depends
+    // on precedence rather than on the input, so they are not compared as
+    // written.
+    #[cfg(feature = "unparen")]
+    if options.remove_redundant_parens {
+        unparen::remove_redundant_parens(&mut file);
+    }
+
+    let mut normalize = Normalize {
+        imports: options.reorder_imports
+            || options.imports_granularity != ImportsGranularity::Preserve,
+    };
+    normalize.visit_file_mut(&mut file);
+
+    // Then put parentheses around every expression, so that the tokens spell
+    // out the structure of the syntax tree on their own, rather than relying
+    // on syn to print the parentheses that precedence requires.
+    #[cfg(feature = "unparen")]
+    if options.remove_redundant_parens {
+        Parenthesize.visit_file_mut(&mut file);
+    }
+
+    file
+}
+
+#[cfg(feature = "unparen")]
+struct Parenthesize;
+
+#[cfg(feature = "unparen")]
+impl VisitMut for Parenthesize {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        visit_mut::visit_expr_mut(self, expr);
+        let inner = mem::replace(expr, Expr::Verbatim(TokenStream::new()));
+        *expr = Expr::Paren(ExprParen {
+            attrs: Vec::new(),
+            paren_token: token::Paren::default(),
+            expr: Box::new(inner),
+        });
+    }
+}
+
+// Use items are sorted, merged and split up by the imports options, so they
+// are compared as a sorted list of the paths they import, one path per item.
+fn normalize_uses(items: &mut Vec<Item>) {
+    let mut uses = Vec::new();
+    items.retain(|item| match item {
+        Item::Use(item) => {
+            split_use(item, &item.tree, &mut Vec::new(), &mut uses);
+            false
+        }
+        _ => true,
+    });
+    uses.sort_by_cached_key(|item| item.to_token_stream().to_string());
+    uses.dedup_by_key(|item| item.to_token_stream().to_string());
+    items.splice(0..0, uses.into_iter().map(Item::Use));
+}
+
+fn split_use(item: &ItemUse, tree: &UseTree, path: &mut Vec<Ident>, uses: &mut Vec<ItemUse>) {
+    let mut path = match tree {
+        UseTree::Path(use_path) => {
+            path.push(use_path.ident.clone());
+            split_use(item, &use_path.tree, path, uses);
+            path.pop();
+            return;
+        }
+        UseTree::Group(use_group) => {
+            for tree in &use_group.items {
+                split_use(item, tree, path, uses);
+            }
+            return;
+        }
+        UseTree::Name(_) | UseTree::Rename(_) | UseTree::Glob(_) => path.clone(),
+    };
+    let mut tree = tree.clone();
+    // `use a::{self, b};` is printed for `use a;` together with `use a::b;`.
+    if let Some(ident) = path.pop() {
+        match &tree {
+            UseTree::Name(use_name) if use_name.ident == "self" => {
+                tree = UseTree::Name(UseName { ident });
+            }
+            UseTree::Rename(use_rename) if use_rename.ident == "self" => {
+                tree = UseTree::Rename(UseRename {
+                    ident,
+                    as_token: use_rename.as_token,
+                    rename: use_rename.rename.clone(),
+                });
+            }
+            _ => path.push(ident),
+        }
+    }
+    for ident in path.into_iter().rev() {
+        tree = UseTree::Path(UsePath {
+            ident,
+            colon2_token: token::PathSep::default(),
+            tree: Box::new(tree),
+        });
+    }
+    uses.push(ItemUse {
+        tree,
+        ..item.clone()
+    });
+}
+
+// Remove the trailing comma from the arguments of a derive attribute, at the
+// top level if `derive` is true and in any `derive(...)` nested inside.
+fn normalize_derives(tokens: TokenStream, derive: bool) -> TokenStream {
+    let mut tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    if derive {
+        if let Some(TokenTree::Punct(punct)) = tokens.last() {
+            if punct.as_char() == ',' {
+                tokens.pop();
+            }
+        }
+    }
+    let mut after_derive = false;
+    for token in &mut tokens {
+        if let TokenTree::Group(group) = token {
+            let stream = normalize_derives(group.stream(), after_derive);
+            let mut normalized = Group::new(group.delimiter(), stream);
+            normalized.set_span(group.span());
+            *group = normalized;
+        }
+        after_derive = matches!(token, TokenTree::Ident(ident) if ident == "derive");
+    }
+    tokens.into_iter().collect()
+}
+
+// Strip the braces that an arm or closure body may be wrapped in when it does
+// not fit on one line.
+fn unwrap_body(body: &mut Expr) {
+    while let Expr::Block(expr) = body {
+        if !expr.attrs.is_empty() || expr.label.is_some() || expr.block.stmts.len() != 1 {
+            return;
+        }
+        match expr.block.stmts.pop().unwrap() {
+            Stmt::Expr(inner, _semi) => *body = inner,
+            Stmt::Macro(stmt) if stmt.attrs.is_empty() => {
+                *body = Expr::Macro(ExprMacro {
+                    attrs: Vec::new(),
+                    mac: stmt.mac,
+                });
+            }
+            stmt => {
+                expr.block.stmts.push(stmt);
+                return;
+            }
+        }
+    }
+}
+
+// Doc comments may be wrapped or split up onto more lines, so consecutive doc
+// attributes are compared as one, by their words.
+fn join_docs(tokens: &mut Vec<Token>) {
+    let mut joined = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let (len, doc) = match doc_attr(&tokens[i..]) {
+            Some(doc_attr) => doc_attr,
+            None => {
+                joined.push(mem::replace(&mut tokens[i], Token::Ident(String::new())));
+                i += 1;
+                continue;
+            }
+        };
+        let inner = matches!(tokens[i + 1], Token::Punct('!', _));
+        let mut words: Vec<String> = doc.split_whitespace().map(str::to_owned).collect();
+        i += len;
+        while let Some((len, doc)) = doc_attr(&tokens[i..]) {
+            if matches!(tokens[i + 1], Token::Punct('!', _)) != inner {
+                break;
+            }
+            words.extend(doc.split_whitespace().map(str::to_owned));
+            i += len;
+        }
+        joined.push(Token::Punct('#', Spacing::Alone));
+        if inner {
+            joined.push(Token::Punct('!', Spacing::Alone));
+        }
+        joined.push(Token::Open('['));
+        joined.push(Token::Ident("doc".to_owned()));
+        joined.push(Token::Punct('=', Spacing::Alone));
+        joined.push(Token::Str(words.join(" ")));
+        joined.push(Token::Close(']'));
+    }
+    *tokens = joined;
+}
+
+// Recognize `#[doc = "..."]` or `#![doc = "..."]` at the start of the tokens,
+// returning the number of tokens it takes up and the text of the doc comment.
+fn doc_attr(tokens: &[Token]) -> Option<(usize, String)> {
+    let start = match tokens {
+        [Token::Punct('#', _), Token::Punct('!', _), ..] => 2,
+        [Token::Punct('#', _), ..] => 1,
+        _ => return None,
+    };
+    match tokens.get(start..start + 5)? {
+        [Token::Open('['), Token::Ident(ident), Token::Punct('=', _), Token::Str(doc), Token::Close(']')]
+            if ident == "doc" =>
+        {
+            Some((start + 5, doc.clone()))
+        }
+        _ => None,
+    }
+}
+
+#[derive(PartialEq)]
+enum Token {
+    Open(char),
+    Close(char),
+    Ident(String),
+    Punct(char, Spacing),
+    Literal(String),
+    // The value of a string literal, which may be written with different
+    // escapes or as a raw string by the printer.
+    Str(String),
+}
+
+impl Display for Token {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Open(ch) | Token::Close(ch) | Token::Punct(ch, _) => write!(formatter, "{}", ch),
+            Token::Ident(string) | Token::Literal(string) => formatter.write_str(string),
+            Token::Str(value) => write!(formatter, "{:?}", value),
+        }
+    }
+}
+
+// A line break in a multi-line string literal that was printed as \r\n means
+// \n, the same way rustc reads source files with \r\n line endings. A block
+// doc comment keeps the \r when it is reparsed, so its value is normalized the
+// same way, on both sides so as to not mistake an escaped \r\n for a change.
+fn crlf_literals(tokens: &mut [Token]) {
+    for token in tokens {
+        if let Token::Literal(string) | Token::Str(string) = token {
+            if string.contains('\r') {
+                *string = string.replace("\r\n", "\n");
+            }
+        }
+    }
+}
+
+// Lay out a token stream as a flat sequence without spans, normalizing the
+// differences that are not significant to the syntax tree: invisible groups
+// and how the value of a string literal is escaped.
+fn flatten(tokens: TokenStream, out: &mut Vec<Token>) {
+    for token in tokens {
+        match token {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ('(', ')'),
+                    Delimiter::Brace => ('{', '}'),
+                    Delimiter::Bracket => ('[', ']'),
+                    Delimiter::None => {
+                        flatten(group.stream(), out);
+                        continue;
+                    }
+                };
+                out.push(Token::Open(open));
+                flatten(group.stream(), out);
+                out.push(Token::Close(close));
+            }
+            TokenTree::Ident(ident) => out.push(Token::Ident(ident.to_string())),
+            TokenTree::Punct(punct) => out.push(Token::Punct(punct.as_char(), punct.spacing())),
+            TokenTree::Literal(literal) => out.push(match Lit::new(literal.clone()) {
+                Lit::Str(lit) if lit.suffix().is_empty() => Token::Str(lit.value()),
+                _ => Token::Literal(literal.to_string()),
+            }),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use std::fmt;
+use std::io;
+
+// Adapter for writing formatted output into an io::Write, holding on to the
+// underlying io::Error since fmt::Error cannot carry one.
+pub struct IoWriter<W> {
+    inner: W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> IoWriter<W> {
+    pub fn new(inner: W) -> Self {
+        IoWriter { inner, error: None }
+    }
+
+    pub fn into_error(self) -> io::Error {
+        self.error
+            .unwrap_or_else(|| io::Error::new(io::ErrorKind::Other, "formatter error"))
+    }
+}
+
+impl<W: io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        self.inner.write_all(string.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
@@ -0,0 +1,8 @@
+use prettyplease::Options;
+
+#[track_caller]
+pub fn check(options: &Options, input: &str, expected: &str) {
+    let syntax_tree = syn::parse_file(input).unwrap();
+    let formatted = prettyplease::unparse_with(&syntax_tree, options);
+    assert_eq!(formatted, expected);
+}
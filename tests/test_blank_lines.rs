@@ -0,0 +1,129 @@
+mod common;
+
+use crate::common::check;
+use prettyplease::Options;
+
+fn options() -> Options {
+    let mut options = Options::default();
+    options.blank_lines = 1;
+    options
+}
+
+#[test]
+fn multi_line_nodes() {
+    // Without the source text, every line between two nodes that is not
+    // covered by either of them counts as blank, so this catches a node whose
+    // first or last line is misjudged.
+    check(
+        &options(),
+        r#"#[derive(
+    Debug,
+)]
+pub(crate) struct S {
+    a: u8,
+}
+impl S {
+    pub unsafe fn f(
+        &self,
+    ) {
+        'outer: loop {
+            break 'outer;
+        }
+        if a {
+            b();
+        } else if c {
+            d();
+        } else {
+            e();
+        }
+        m! {
+            x
+        }
+        let _ = [
+            0,
+        ];
+        g();
+    }
+    const C: u8 = 0;
+}
+m! {
+    y
+}
+enum E {
+    A {
+        x: u8,
+    },
+    B(
+        u8,
+    ),
+    C = 1,
+}
+"#,
+        r#"#[derive(Debug)]
+pub(crate) struct S {
+    a: u8,
+}
+impl S {
+    pub unsafe fn f(&self) {
+        'outer: loop {
+            break 'outer;
+        }
+        if a {
+            b();
+        } else if c {
+            d();
+        } else {
+            e();
+        }
+        m! {
+            x
+        }
+        let _ = [0];
+        g();
+    }
+    const C: u8 = 0;
+}
+m! {
+    y
+}
+enum E {
+    A { x: u8 },
+    B(u8),
+    C = 1,
+}
+"#,
+    );
+}
+
+#[test]
+fn between_nodes() {
+    let input = r#"use std::fmt;
+
+struct S {
+    a: u8,
+
+    b: u8,
+}
+
+enum E {
+    A {
+        x: u8,
+
+        y: u8,
+    },
+
+    B,
+}
+
+fn f() {
+    let x = 1;
+
+    if x == 1 {
+        g();
+    }
+
+    h();
+}
+"#;
+    check(&options(), input, input);
+}
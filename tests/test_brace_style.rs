@@ -0,0 +1,68 @@
+mod common;
+
+use crate::common::check;
+use prettyplease::{BraceStyle, Options};
+
+const INPUT: &str = "
+    fn f() { x }
+    fn g() {}
+    fn h<T>(t: T) -> T where T: Clone { t }
+    struct A<T> where T: Clone, T: Copy { t: T }
+    struct B<T> where T: Clone;
+    enum E { A }
+    enum F {}
+    impl<T> Tr for A<T> where T: Clone { fn x(&self) {} }
+    trait Tr<T> { fn x(&self) where T: Copy; fn y() { z } }
+";
+
+#[test]
+fn test_same_line_where() {
+    let expected = "\
+        fn f() {\n    x\n}\n\
+        fn g() {}\n\
+        fn h<T>(t: T) -> T\nwhere\n    T: Clone,\n{\n    t\n}\n\
+        struct A<T>\nwhere\n    T: Clone,\n    T: Copy,\n{\n    t: T,\n}\n\
+        struct B<T>\nwhere\n    T: Clone;\n\
+        enum E {\n    A,\n}\n\
+        enum F {}\n\
+        impl<T> Tr for A<T>\nwhere\n    T: Clone,\n{\n    fn x(&self) {}\n}\n\
+        trait Tr<T> {\n    fn x(&self)\n    where\n        T: Copy;\n    fn y() {\n        z\n    }\n}\n\
+    ";
+    check(&Options::default(), INPUT, expected);
+}
+
+#[test]
+fn test_always_next_line() {
+    let mut options = Options::default();
+    options.brace_style = BraceStyle::AlwaysNextLine;
+    let expected = "\
+        fn f()\n{\n    x\n}\n\
+        fn g() {}\n\
+        fn h<T>(t: T) -> T\nwhere\n    T: Clone,\n{\n    t\n}\n\
+        struct A<T>\nwhere\n    T: Clone,\n    T: Copy,\n{\n    t: T,\n}\n\
+        struct B<T>\nwhere\n    T: Clone;\n\
+        enum E\n{\n    A,\n}\n\
+        enum F {}\n\
+        impl<T> Tr for A<T>\nwhere\n    T: Clone,\n{\n    fn x(&self) {}\n}\n\
+        trait Tr<T>\n{\n    fn x(&self)\n    where\n        T: Copy;\n    fn y()\n    {\n        z\n    }\n}\n\
+    ";
+    check(&options, INPUT, expected);
+}
+
+#[test]
+fn test_where_single_line() {
+    let mut options = Options::default();
+    options.where_single_line = true;
+    let expected = "\
+        fn f() {\n    x\n}\n\
+        fn g() {}\n\
+        fn h<T>(t: T) -> T\nwhere T: Clone\n{\n    t\n}\n\
+        struct A<T>\nwhere\n    T: Clone,\n    T: Copy,\n{\n    t: T,\n}\n\
+        struct B<T>\nwhere T: Clone;\n\
+        enum E {\n    A,\n}\n\
+        enum F {}\n\
+        impl<T> Tr for A<T>\nwhere T: Clone\n{\n    fn x(&self) {}\n}\n\
+        trait Tr<T> {\n    fn x(&self)\n    where T: Copy;\n    fn y() {\n        z\n    }\n}\n\
+    ";
+    check(&options, INPUT, expected);
+}
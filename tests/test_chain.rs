@@ -0,0 +1,92 @@
+mod common;
+
+use crate::common::check;
+use prettyplease::Options;
+
+const INPUT: &str = r#"
+    fn f() {
+        let app = Command::new("app").arg(Arg::new("verbose").short('v')).get_matches();
+        let body = client.get(url).send().await?.text().await?;
+        let len = s.trim().len();
+        let name = self.config.name.clone();
+        let x = v.len();
+    }
+"#;
+
+#[test]
+fn test_default() {
+    let expected = r#"fn f() {
+    let app = Command::new("app").arg(Arg::new("verbose").short('v')).get_matches();
+    let body = client.get(url).send().await?.text().await?;
+    let len = s.trim().len();
+    let name = self.config.name.clone();
+    let x = v.len();
+}
+"#;
+    check(&Options::default(), INPUT, expected);
+}
+
+#[test]
+fn test_chain_width() {
+    let mut options = Options::default();
+    options.chain_width = 30;
+    let expected = r#"fn f() {
+    let app = Command::new("app")
+        .arg(Arg::new("verbose").short('v'))
+        .get_matches();
+    let body = client
+        .get(url)
+        .send()
+        .await?
+        .text()
+        .await?;
+    let len = s.trim().len();
+    let name = self.config.name.clone();
+    let x = v.len();
+}
+"#;
+    check(&options, INPUT, expected);
+}
+
+#[test]
+fn test_one_call_per_line() {
+    let mut options = Options::default();
+    options.chain_one_call_per_line = true;
+    let expected = r#"fn f() {
+    let app = Command::new("app")
+        .arg(Arg::new("verbose").short('v'))
+        .get_matches();
+    let body = client
+        .get(url)
+        .send()
+        .await?
+        .text()
+        .await?;
+    let len = s
+        .trim()
+        .len();
+    let name = self.config.name.clone();
+    let x = v.len();
+}
+"#;
+    check(&options, INPUT, expected);
+}
+
+#[test]
+fn test_closure_body() {
+    let mut options = Options::default();
+    options.max_width = 40;
+    options.min_space = 0;
+    let input = "fn f() { map.with(|sm| sm.borrow_mut().fileinfo(span).text(span)) }";
+    let expected = "\
+        fn f() {\n\
+        \x20   map.with(|sm| {\n\
+        \x20       sm.borrow_mut()\n\
+        \x20           .fileinfo(span)\n\
+        \x20           .text(span)\n\
+        \x20   })\n\
+        }\n\
+    ";
+    check(&options, input, expected);
+    check(&options, expected, expected);
+}
@@ -0,0 +1,169 @@
+#[track_caller]
+fn check(input: &str, expected: &str) {
+    let syntax_tree = syn::parse_file(input).unwrap();
+    let options = prettyplease::Options::default();
+    let formatted = prettyplease::unparse_with_comments(&syntax_tree, input, &options);
+    assert_eq!(formatted, expected);
+}
+
+#[test]
+fn binary_expr_stmt() {
+    check(
+        "fn f() {\n    x + y; // c\n}\n",
+        "fn f() {\n    // c\n    x + y;\n}\n",
+    );
+    check(
+        "fn f() {\n    // c\n    x = y;\n    // d\n    x..y;\n    // e\n    x as u8;\n}\n",
+        "fn f() {\n    // c\n    x = y;\n    // d\n    x..y;\n    // e\n    x as u8;\n}\n",
+    );
+}
+
+#[test]
+fn comment_markers_in_literals() {
+    let input = r####"
+fn f() {
+    let s = "// not a comment /* either */";
+    let c = '/';
+    let star = '*';
+    let quote = '"';
+    let r = r#"// still "a" string /*"#;
+    let b = b"/* bytes */";
+    let br = br##"// raw bytes"##;
+    let lifetime: &'static str = "\"// escaped quote";
+}
+"####;
+    check(input, &input[1..]);
+}
+
+#[test]
+fn nested_block_comment() {
+    check(
+        "fn f() {\n    /* outer /* inner */ still outer */\n    g();\n}\n",
+        "fn f() {\n    // outer /* inner */ still outer\n    g();\n}\n",
+    );
+}
+
+#[test]
+fn end_of_file() {
+    check(
+        "fn f() {}\n// after the last item\n",
+        "fn f() {}\n// after the last item\n",
+    );
+    check(
+        "fn f() {}\n\n/* at the end */",
+        "fn f() {}\n// at the end\n",
+    );
+    check(
+        "fn f() {\n    g();\n    // after the last statement\n}\n",
+        "fn f() {\n    g();\n    // after the last statement\n}\n",
+    );
+}
+
+#[test]
+fn end_of_scope() {
+    let input = r#"
+struct S {
+    a: u8,
+    // after the last field
+}
+enum E {
+    V {
+        a: u8,
+        /* in a variant */
+    },
+    // after the last variant
+}
+impl S {
+    fn f() {}
+    // after the last impl item
+}
+mod m {
+    fn f() {
+        // in an empty block
+    }
+    // after the last item
+}
+fn f() {
+    match x {
+        _ => {}
+        // after the last arm
+    }
+    let y = || {
+        z
+        // after the value of the block
+    };
+}
+fn g() {}
+"#;
+    let expected = r#"
+struct S {
+    a: u8,
+    // after the last field
+}
+enum E {
+    V {
+        a: u8,
+        // in a variant
+    },
+    // after the last variant
+}
+impl S {
+    fn f() {}
+    // after the last impl item
+}
+mod m {
+    fn f() {
+        // in an empty block
+    }
+    // after the last item
+}
+fn f() {
+    match x {
+        _ => {}
+        // after the last arm
+    }
+    let y = || {
+        z
+        // after the value of the block
+    };
+}
+fn g() {}
+"#;
+    check(input, &expected[1..]);
+}
+
+#[test]
+fn within_expression() {
+    check(
+        "fn f() {\n    let x = g(\n        // c\n        1,\n    );\n}\nfn h() {}\n",
+        "fn f() {\n    let x = g(1);\n    // c\n}\nfn h() {}\n",
+    );
+    check(
+        "fn f() {\n    let x = g(\n        // c\n        1,\n    );\n    h();\n}\n",
+        "fn f() {\n    let x = g(1);\n    // c\n    h();\n}\n",
+    );
+}
+
+#[test]
+fn trailing_comments() {
+    check(
+        "struct S {\n    a: u8, // first\n    b: u8, // second\n}\n",
+        "struct S {\n    // first\n    a: u8,\n    // second\n    b: u8,\n}\n",
+    );
+    check(
+        "fn f() {\n    let x = 1; // one\n    match x {\n        _ => {} // arm\n    }\n}\n",
+        "fn f() {\n    // one\n    let x = 1;\n    match x {\n        // arm\n        _ => {}\n    }\n}\n",
+    );
+}
+
+#[test]
+fn next_to_doc_comments() {
+    check(
+        "// Ordinary.\n/// Doc.\n// Between.\n/** Block doc. */\n/* Block. */\nfn f() {}\n",
+        "// Ordinary.\n/// Doc.\n// Between.\n/// Block doc.\n// Block.\nfn f() {}\n",
+    );
+    check(
+        "//! Inner doc.\n// Ordinary.\n\n//// Not a doc comment.\n//** Not a doc comment either.\nfn f() {}\n",
+        "//! Inner doc.\n// Ordinary.\n//// Not a doc comment.\n//** Not a doc comment either.\nfn f() {}\n",
+    );
+}
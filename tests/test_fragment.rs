@@ -0,0 +1,83 @@
+use prettyplease::{BraceStyle, Options};
+use syn::parse::Parser as _;
+use syn::{Block, Expr, Item, Pat, Stmt, Type};
+
+#[test]
+fn test_defaults() {
+    let expr: Expr = syn::parse_str("f(first_argument, second_argument)").unwrap();
+    assert_eq!(
+        prettyplease::unparse_expr(&expr),
+        prettyplease::unparse_expr_with(&expr, &Options::default()),
+    );
+}
+
+#[test]
+fn test_trailing_newline() {
+    // Items and statements are printed as whole lines, the way they are in a
+    // file. The other fragments end at their last token.
+    let item: Item = syn::parse_str("fn f() { g(); }").unwrap();
+    assert_eq!(prettyplease::unparse_item(&item), "fn f() {\n    g();\n}\n");
+
+    let stmt: Stmt = syn::parse_str("let x = y;").unwrap();
+    assert_eq!(prettyplease::unparse_stmt(&stmt), "let x = y;\n");
+
+    let expr: Expr = syn::parse_str("x+y").unwrap();
+    assert_eq!(prettyplease::unparse_expr(&expr), "x + y");
+
+    let ty: Type = syn::parse_str("Vec<u8>").unwrap();
+    assert_eq!(prettyplease::unparse_type(&ty), "Vec<u8>");
+
+    let pat: Pat = Pat::parse_single.parse_str("Some(x)").unwrap();
+    assert_eq!(prettyplease::unparse_pat(&pat), "Some(x)");
+
+    let block: Block = syn::parse_str("{ g(); }").unwrap();
+    assert_eq!(prettyplease::unparse_block(&block), "{\n    g();\n}");
+}
+
+#[test]
+fn test_options() {
+    let mut options = Options::default();
+    options.max_width = 30;
+    options.min_space = 0;
+    options.hard_tabs = true;
+    options.brace_style = BraceStyle::AlwaysNextLine;
+
+    let item: Item = syn::parse_str("fn f() { g(first_argument, second_argument); }").unwrap();
+    assert_eq!(
+        prettyplease::unparse_item_with(&item, &options),
+        "fn f()\n{\n\tg(\n\t\tfirst_argument,\n\t\tsecond_argument,\n\t);\n}\n",
+    );
+
+    let expr: Expr = syn::parse_str("f(first_argument, second_argument)").unwrap();
+    assert_eq!(
+        prettyplease::unparse_expr_with(&expr, &options),
+        "f(\n\tfirst_argument,\n\tsecond_argument,\n)",
+    );
+
+    let ty: Type = syn::parse_str("(FirstType, SecondType, ThirdType)").unwrap();
+    assert_eq!(
+        prettyplease::unparse_type_with(&ty, &options),
+        "(\n\tFirstType,\n\tSecondType,\n\tThirdType,\n)",
+    );
+
+    let pat: Pat = Pat::parse_single
+        .parse_str("Struct { first_field, second_field }")
+        .unwrap();
+    assert_eq!(
+        prettyplease::unparse_pat_with(&pat, &options),
+        "Struct {\n\tfirst_field,\n\tsecond_field,\n}",
+    );
+
+    let stmt: Stmt = syn::parse_str("let x = f(first_argument, second_argument);").unwrap();
+    assert_eq!(
+        prettyplease::unparse_stmt_with(&stmt, &options),
+        "let x = f(\n\tfirst_argument,\n\tsecond_argument,\n);\n",
+    );
+
+    let block: Block =
+        syn::parse_str("{ f(first_argument, second_argument, third_argument) }").unwrap();
+    assert_eq!(
+        prettyplease::unparse_block_with(&block, &options),
+        "{\n\tf(\n\t\tfirst_argument,\n\t\tsecond_argument,\n\t\tthird_argument,\n\t)\n}",
+    );
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use crate::common::check;
+use prettyplease::Options;
+
+#[test]
+fn test_hard_tabs() {
+    let mut options = Options::default();
+    options.hard_tabs = true;
+    let input = "
+        mod m {
+            fn f() {
+                let x = aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa + bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb + cccccccccccccccccccccccc;
+                match x { 1 => {} _ => g(x) }
+            }
+        }
+    ";
+    let expected = "\
+        mod m {\n\
+        \tfn f() {\n\
+        \t\tlet x = aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa + bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb\n\
+        \t\t\t+ cccccccccccccccccccccccc;\n\
+        \t\tmatch x {\n\
+        \t\t\t1 => {}\n\
+        \t\t\t_ => g(x),\n\
+        \t\t}\n\
+        \t}\n\
+        }\n\
+    ";
+    check(&options, input, expected);
+}
+
+#[test]
+fn test_tab_width() {
+    let mut options = Options::default();
+    options.hard_tabs = true;
+    options.tab_spaces = 8;
+    options.max_width = 45;
+    options.min_space = 0;
+    let input = "fn f() { g(aaaaaaaaaa, bbbbbbbbbb, cccccccccc); }";
+    let expected = "\
+        fn f() {\n\
+        \tg(\n\
+        \t\taaaaaaaaaa,\n\
+        \t\tbbbbbbbbbb,\n\
+        \t\tcccccccccc,\n\
+        \t);\n\
+        }\n\
+    ";
+    check(&options, input, expected);
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use crate::common::check;
+use prettyplease::{Options, SmallHeuristics};
+
+const INPUT: &str = "
+    fn f() {
+        let p = Point { x: 1, y: 2 };
+        let e = Empty {};
+        let a = [first, second, third];
+        g(first, second, third);
+        s.trim().to_owned();
+    }
+";
+
+#[test]
+fn test_default() {
+    let expected = "\
+        fn f() {\n\
+        \x20   let p = Point { x: 1, y: 2 };\n\
+        \x20   let e = Empty {};\n\
+        \x20   let a = [first, second, third];\n\
+        \x20   g(first, second, third);\n\
+        \x20   s.trim().to_owned();\n\
+        }\n\
+    ";
+    check(&Options::default(), INPUT, expected);
+}
+
+#[test]
+fn test_widths() {
+    let mut options = Options::default();
+    options.struct_lit_width = 10;
+    options.array_width = 10;
+    options.fn_call_width = 10;
+    options.chain_width = 10;
+    let expected = "\
+        fn f() {\n\
+        \x20   let p = Point {\n        x: 1,\n        y: 2,\n    };\n\
+        \x20   let e = Empty {};\n\
+        \x20   let a = [\n        first,\n        second,\n        third,\n    ];\n\
+        \x20   g(\n        first,\n        second,\n        third,\n    );\n\
+        \x20   s.trim()\n        .to_owned();\n\
+        }\n\
+    ";
+    check(&options, INPUT, expected);
+}
+
+#[test]
+fn test_off() {
+    let mut options = Options::default();
+    options.use_small_heuristics = SmallHeuristics::Off;
+    options.array_width = 10;
+    let expected = "\
+        fn f() {\n\
+        \x20   let p = Point {\n        x: 1,\n        y: 2,\n    };\n\
+        \x20   let e = Empty {};\n\
+        \x20   let a = [first, second, third];\n\
+        \x20   g(first, second, third);\n\
+        \x20   s.trim().to_owned();\n\
+        }\n\
+    ";
+    check(&options, INPUT, expected);
+}
+
+#[test]
+fn test_max() {
+    let input = "fn f() { let point = Point { x: 1, y: 2, z: 3, w: 4, v: 5 }; }";
+    let expected = "\
+        fn f() {\n\
+        \x20   let point = Point {\n        x: 1,\n        y: 2,\n        z: 3,\n        w: 4,\n        v: 5,\n    };\n\
+        }\n\
+    ";
+    check(&Options::default(), input, expected);
+
+    let mut options = Options::default();
+    options.use_small_heuristics = SmallHeuristics::Max;
+    let expected = "\
+        fn f() {\n\
+        \x20   let point = Point { x: 1, y: 2, z: 3, w: 4, v: 5 };\n\
+        }\n\
+    ";
+    check(&options, input, expected);
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use crate::common::check;
+use prettyplease::{ImportsGranularity, Options};
+
+fn reorder() -> Options {
+    let mut options = Options::default();
+    options.reorder_imports = true;
+    options
+}
+
+fn granularity(imports_granularity: ImportsGranularity) -> Options {
+    let mut options = reorder();
+    options.imports_granularity = imports_granularity;
+    options
+}
+
+#[test]
+fn test_reorder() {
+    let input = "
+        use std::io::{Write, self, Read as R, Read};
+        use crate::foo;
+        use ::std::mem;
+        use a::{d, *, b::c, b};
+        use self::bar;
+        fn f() {}
+        use z;
+        use y;
+        mod m {
+            use b;
+            use a;
+        }
+    ";
+    let expected = "\
+        use self::bar;\n\
+        use crate::foo;\n\
+        use ::std::mem;\n\
+        use a::{b, b::c, d, *};\n\
+        use std::io::{self, Read, Read as R, Write};\n\
+        fn f() {}\n\
+        use y;\n\
+        use z;\n\
+        mod m {\n    use a;\n    use b;\n}\n\
+    ";
+    check(&reorder(), input, expected);
+}
+
+#[test]
+fn test_version_sort() {
+    let input = "use a::{x86_64, u16, u8, U8, _x, x86, v01, v1, v001, usize};";
+    let expected = "use a::{_x, U8, u8, u16, usize, v001, v01, v1, x86, x86_64};\n";
+    check(&reorder(), input, expected);
+}
+
+#[test]
+fn test_rename() {
+    let input = "use a::{b as c, b};";
+    let expected = "use a::{b, b as c};\n";
+    check(&reorder(), input, expected);
+}
+
+#[test]
+fn test_braced_self() {
+    let input = "use a::{self}; use b::{c::{self}};";
+    let expected = "use a::{self};\nuse b::c::{self};\n";
+    check(&Options::default(), input, expected);
+    check(&reorder(), input, expected);
+}
+
+#[test]
+fn test_granularity() {
+    let input = "
+        use a::b::c;
+        use a::b::d;
+        use a;
+        pub use a::e;
+        #[cfg(test)]
+        use a::{f, g};
+        use a::b::c;
+        use x::{y, z::w};
+    ";
+    check(
+        &granularity(ImportsGranularity::Crate),
+        input,
+        "\
+        pub use a::e;\n\
+        use a::{self, b::{c, d}};\n\
+        #[cfg(test)]\n\
+        use a::{f, g};\n\
+        use x::{y, z::w};\n\
+        ",
+    );
+    check(
+        &granularity(ImportsGranularity::Module),
+        input,
+        "\
+        use a;\n\
+        use a::b::{c, d};\n\
+        pub use a::e;\n\
+        #[cfg(test)]\n\
+        use a::{f, g};\n\
+        use x::y;\n\
+        use x::z::w;\n\
+        ",
+    );
+    check(
+        &granularity(ImportsGranularity::Item),
+        input,
+        "\
+        use a;\n\
+        use a::b::c;\n\
+        use a::b::d;\n\
+        pub use a::e;\n\
+        #[cfg(test)]\n\
+        use a::f;\n\
+        #[cfg(test)]\n\
+        use a::g;\n\
+        use x::y;\n\
+        use x::z::w;\n\
+        ",
+    );
+}
+
+#[test]
+fn test_granularity_crate_self() {
+    let input = "use std::fmt; use std::fmt::Display; use std::io;";
+    let expected = "use std::{\n    fmt::{self, Display},\n    io,\n};\n";
+    check(&granularity(ImportsGranularity::Crate), input, expected);
+    let input = "use std::fmt; use std::fmt::Display;";
+    let expected = "use std::fmt::{self, Display};\n";
+    check(&granularity(ImportsGranularity::Crate), input, expected);
+}
+
+#[test]
+fn test_granularity_item_rename() {
+    let input = "use std::io::{Read as R, Read};\n";
+    let expected = "use std::io::Read;\nuse std::io::Read as R;\n";
+    check(&granularity(ImportsGranularity::Item), input, expected);
+}
+
+#[cfg(feature = "span-locations")]
+#[test]
+fn test_blank_line() {
+    let mut options = reorder();
+    options.blank_lines = 1;
+    let input = "use b;\nuse a;\n\nuse d;\nuse c;\n";
+    let expected = "use a;\nuse b;\n\nuse c;\nuse d;\n";
+    check(&options, input, expected);
+}
@@ -0,0 +1,188 @@
+mod common;
+
+use crate::common::check;
+use prettyplease::{MatchArmLeadingPipes, Options};
+
+fn options() -> Options {
+    let mut options = Options::default();
+    options.max_width = 60;
+    options.min_space = 0;
+    options
+}
+
+const INPUT: &str = "
+    fn f() {
+        match x {
+            | A | B => {}
+            C => { g(); }
+            Some(Direction::Down) => long_function_name(first, second),
+            _ => if y { 1 } else { 2 },
+        }
+    }
+";
+
+#[test]
+fn test_default() {
+    let expected = "\
+fn f() {
+    match x {
+        A | B => {}
+        C => {
+            g();
+        }
+        Some(Direction::Down) => {
+            long_function_name(first, second)
+        }
+        _ => if y { 1 } else { 2 }
+    }
+}
+";
+    check(&options(), INPUT, expected);
+}
+
+#[test]
+fn test_no_arm_blocks() {
+    let mut options = options();
+    options.match_arm_blocks = false;
+    let expected = "\
+fn f() {
+    match x {
+        A | B => {}
+        C => {
+            g();
+        }
+        Some(Direction::Down) =>
+            long_function_name(first, second),
+        _ => if y { 1 } else { 2 }
+    }
+}
+";
+    check(&options, INPUT, expected);
+
+    let input = "
+        fn f() {
+            match x {
+                D => loop { g(); },
+                E => match y { _ => {} },
+                F => S { first: 1, second: 2, third: 3, fourth: 4 },
+            }
+        }
+    ";
+    let expected = "\
+fn f() {
+    match x {
+        D => loop {
+            g();
+        }
+        E => match y {
+            _ => {}
+        }
+        F => S {
+            first: 1,
+            second: 2,
+            third: 3,
+            fourth: 4,
+        },
+    }
+}
+";
+    check(&options, input, expected);
+
+    options.match_block_trailing_comma = true;
+    let expected = "\
+fn f() {
+    match x {
+        D => loop {
+            g();
+        },
+        E => match y {
+            _ => {},
+        },
+        F => S {
+            first: 1,
+            second: 2,
+            third: 3,
+            fourth: 4,
+        },
+    }
+}
+";
+    check(&options, input, expected);
+}
+
+#[test]
+fn test_leading_pipes() {
+    let mut options = options();
+    options.match_arm_leading_pipes = MatchArmLeadingPipes::Always;
+    let expected = "\
+fn f() {
+    match x {
+        | A | B => {}
+        | C => {
+            g();
+        }
+        | Some(Direction::Down) => {
+            long_function_name(first, second)
+        }
+        | _ => if y { 1 } else { 2 }
+    }
+}
+";
+    check(&options, INPUT, expected);
+
+    options.match_arm_leading_pipes = MatchArmLeadingPipes::Preserve;
+    let expected = "\
+fn f() {
+    match x {
+        | A | B => {}
+        C => {
+            g();
+        }
+        Some(Direction::Down) => {
+            long_function_name(first, second)
+        }
+        _ => if y { 1 } else { 2 }
+    }
+}
+";
+    check(&options, INPUT, expected);
+}
+
+#[test]
+fn test_block_trailing_comma() {
+    let mut options = options();
+    options.match_block_trailing_comma = true;
+    let expected = "\
+fn f() {
+    match x {
+        A | B => {},
+        C => {
+            g();
+        },
+        Some(Direction::Down) => {
+            long_function_name(first, second)
+        },
+        _ => if y { 1 } else { 2 }
+    }
+}
+";
+    check(&options, INPUT, expected);
+}
+
+#[test]
+fn test_leading_pipes_broken() {
+    let mut options = options();
+    options.match_arm_leading_pipes = MatchArmLeadingPipes::Always;
+    let input = "fn f() { match x { Some(FirstVariant) | Some(SecondVariant) | Some(ThirdVariant) | None => {} } }";
+    let expected = "\
+fn f() {
+    match x {
+        | Some(FirstVariant)
+        | Some(SecondVariant)
+        | Some(ThirdVariant)
+        | None => {}
+    }
+}
+";
+    check(&options, input, expected);
+}
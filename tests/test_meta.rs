@@ -0,0 +1,55 @@
+mod common;
+
+use crate::common::check;
+use prettyplease::Options;
+
+#[test]
+fn test_derive() {
+    let input = "
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, Default)]
+        #[derive(Debug, Clone)]
+        struct S;
+    ";
+    let expected = "\
+        #[derive(\n    \
+            Debug,\n    \
+            Clone,\n    \
+            Copy,\n    \
+            PartialEq,\n    \
+            Eq,\n    \
+            Hash,\n    \
+            PartialOrd,\n    \
+            Ord,\n    \
+            serde::Serialize,\n    \
+            Default,\n\
+        )]\n\
+        #[derive(Debug, Clone)]\n\
+        struct S;\n\
+    ";
+    check(&Options::default(), input, expected);
+}
+
+#[test]
+fn test_nested() {
+    let input = r#"
+        #[cfg(all(feature = "some-long-feature-name", any(target_os = "linux", target_os = "macos"), not(test)))]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+        #[repr(C, align(8))]
+        struct S;
+    "#;
+    let expected = "\
+        #[cfg(all(\n    \
+            feature = \"some-long-feature-name\",\n    \
+            any(target_os = \"linux\", target_os = \"macos\"),\n    \
+            not(test)\n\
+        ))]\n\
+        #[cfg_attr(\n    \
+            feature = \"serde\",\n    \
+            derive(serde::Serialize, serde::Deserialize),\n    \
+            serde(rename_all = \"kebab-case\")\n\
+        )]\n\
+        #[repr(C, align(8))]\n\
+        struct S;\n\
+    ";
+    check(&Options::default(), input, expected);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use crate::common::check;
+use prettyplease::{NewlineStyle, Options};
+
+const INPUT: &str = r#"
+    /** Block
+        doc */
+    #[doc = "first\nsecond"]
+    fn f() {
+        let s = "one
+two";
+    }
+"#;
+
+#[test]
+fn test_unix() {
+    let expected =
+        "/** Block\n        doc */\n/**first\nsecond*/\nfn f() {\n    let s = \"one\ntwo\";\n}\n";
+    check(&Options::default(), INPUT, expected);
+}
+
+#[test]
+fn test_windows() {
+    let mut options = Options::default();
+    options.newline_style = NewlineStyle::Windows;
+    let expected = "/** Block\r\n        doc */\r\n/**first\r\nsecond*/\r\nfn f() {\r\n    let s = \"one\r\ntwo\";\r\n}\r\n";
+    check(&options, INPUT, expected);
+
+    // Line endings that are already \r\n are not doubled.
+    check(&options, &INPUT.replace('\n', "\r\n"), expected);
+}
+
+#[test]
+fn test_windows_normalized_doc() {
+    let mut options = Options::default();
+    options.newline_style = NewlineStyle::Windows;
+    options.normalize_doc_attributes = true;
+    let expected = "/// Block\r\n///        doc\r\n///first\r\n///second\r\nfn f() {\r\n    let s = \"one\r\ntwo\";\r\n}\r\n";
+    check(&options, INPUT, expected);
+}
+
+#[test]
+fn test_writer() {
+    let syntax_tree = syn::parse_file(INPUT).unwrap();
+    let mut options = Options::default();
+    options.newline_style = NewlineStyle::Windows;
+    let expected = prettyplease::unparse_with(&syntax_tree, &options);
+
+    let mut bytes = Vec::new();
+    prettyplease::unparse_to_writer_with(&syntax_tree, &options, &mut bytes).unwrap();
+    assert_eq!(String::from_utf8(bytes).unwrap(), expected);
+
+    let mut string = String::new();
+    prettyplease::unparse_to_fmt_writer_with(&syntax_tree, &options, &mut string).unwrap();
+    assert_eq!(string, expected);
+}
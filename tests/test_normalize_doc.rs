@@ -0,0 +1,123 @@
+mod common;
+
+use prettyplease::Options;
+use syn::{Attribute, Expr, ExprLit, File, Item, Lit, Meta};
+
+#[track_caller]
+fn check(input: &str, expected: &str) {
+    let mut options = Options::default();
+    options.normalize_doc_attributes = true;
+    common::check(&options, input, expected);
+    let syntax_tree = syn::parse_file(input).unwrap();
+    let reparsed = syn::parse_file(expected).unwrap();
+    assert_eq!(docs(&reparsed), docs(&syntax_tree));
+}
+
+// The text of the doc attributes on the file, its structs and their fields,
+// with consecutive doc attributes joined the way rustdoc joins them.
+fn docs(file: &File) -> Vec<String> {
+    let mut docs = vec![doc(&file.attrs)];
+    for item in &file.items {
+        if let Item::Struct(item) = item {
+            docs.push(doc(&item.attrs));
+            for field in &item.fields {
+                docs.push(doc(&field.attrs));
+            }
+        }
+    }
+    docs
+}
+
+fn doc(attrs: &[Attribute]) -> String {
+    let mut lines = Vec::new();
+    for attr in attrs {
+        if let Meta::NameValue(meta) = &attr.meta {
+            if let Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }) = &meta.value
+            {
+                lines.push(lit.value());
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+#[test]
+fn test_block_comment() {
+    let input = "
+/**
+ * Star block.
+ *
+ *     code
+*/
+struct S;
+";
+    let expected = "\
+        ///\n\
+        /// * Star block.\n\
+        /// *\n\
+        /// *     code\n\
+        ///\n\
+        struct S;\n\
+    ";
+    check(input, expected);
+}
+
+#[test]
+fn test_doc_attribute() {
+    let input = r#"
+        #![doc = "inner\n  doc"]
+        #[doc = "\n   Indented\n     more\n   back"]
+        struct S {
+            #[doc = " a\n b"]
+            x: u8,
+        }
+    "#;
+    let expected = "\
+        //!inner\n\
+        //!  doc\n\
+        ///\n\
+        ///   Indented\n\
+        ///     more\n\
+        ///   back\n\
+        struct S {\n    \
+            /// a\n    \
+            /// b\n    \
+            x: u8,\n\
+        }\n\
+    ";
+    check(input, expected);
+}
+
+#[test]
+fn test_line_per_line() {
+    let input = r#"
+        #[doc = "\n"]
+        #[doc = "x\n"]
+        struct S;
+    "#;
+    let expected = "\
+        ///\n\
+        ///\n\
+        ///x\n\
+        ///\n\
+        struct S;\n\
+    ";
+    check(input, expected);
+}
+
+#[test]
+fn test_not_doc_lines() {
+    let input = r#"
+        #[doc = "/ slash"]
+        #[doc = "x\r\ny"]
+        struct S;
+    "#;
+    let expected = "\
+        #[doc = \"/ slash\"]\n\
+        /**x\r\ny*/\n\
+        struct S;\n\
+    ";
+    check(input, expected);
+}
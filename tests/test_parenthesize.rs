@@ -0,0 +1,115 @@
+// Syntax trees built by code generators often leave out the parentheses that
+// operator precedence calls for. These tests strip every parenthesized
+// expression from the input and check that the printer puts back exactly the
+// parentheses needed for the output to parse to the same syntax tree.
+
+use syn::visit_mut::{self, VisitMut};
+use syn::{Expr, File};
+
+fn strip_parens(mut file: File) -> File {
+    struct StripParens;
+
+    impl VisitMut for StripParens {
+        fn visit_expr_mut(&mut self, expr: &mut Expr) {
+            while let Expr::Paren(paren) = expr {
+                if !paren.attrs.is_empty() {
+                    break;
+                }
+                *expr = (*paren.expr).clone();
+            }
+            visit_mut::visit_expr_mut(self, expr);
+        }
+    }
+
+    StripParens.visit_file_mut(&mut file);
+    file
+}
+
+#[track_caller]
+fn check(stmt: &str) {
+    let source = format!("fn f() {{\n    {}\n}}\n", stmt);
+    let syntax_tree = strip_parens(syn::parse_file(&source).unwrap());
+    let formatted = prettyplease::unparse(&syntax_tree);
+    assert_eq!(formatted, source);
+    let reparsed = strip_parens(syn::parse_file(&formatted).unwrap());
+    assert!(
+        reparsed == syntax_tree,
+        "different syntax tree:\n{}",
+        formatted
+    );
+}
+
+#[test]
+fn binary() {
+    check("let _ = (a + b) * c;");
+    check("let _ = a - (b - c);");
+    check("let _ = a - b - c;");
+    check("let _ = (a < b) == c;");
+    check("let _ = a || b && c;");
+    check("let _ = (a || b) && c;");
+    check("let _ = (a as u8) < b;");
+    check("let _ = (a as u8) << b;");
+    check("let _ = a as u8 + b;");
+    check("let _ = (a + b) as u8;");
+}
+
+#[test]
+fn prefix() {
+    check("let _ = -(a + b);");
+    check("let _ = (-a).pow(2);");
+    check("let _ = -a.pow(2);");
+    check("let _ = &(a + b);");
+    check("let _ = (*a)[0];");
+    check("let _ = !(a == b);");
+}
+
+#[test]
+fn postfix() {
+    check("let _ = (a..b).len();");
+    check("let _ = (a.b)();");
+    check("let _ = a.b();");
+    check("let _ = (|x| x)(1);");
+    check("let _ = (a + b)?;");
+    check("let _ = (&a).field;");
+}
+
+#[test]
+fn assign() {
+    check("a = b = c;");
+    check("(a = b) = c;");
+    check("a += b + c;");
+    check("let _ = a..(b = c);");
+}
+
+#[test]
+fn range() {
+    check("let _ = (a..b)..c;");
+    check("let _ = .. ..=a;");
+    check("let _ = (..a).len();");
+    check("let _ = a..b + c;");
+}
+
+#[test]
+fn jump() {
+    check("let _ = (|| a) + b;");
+    check("let _ = a + || b;");
+    check("let _ = (return) - 1;");
+    check("let _ = return + 1;");
+    check("let _ = a + return b;");
+    check("let _ = (return a) + b;");
+}
+
+#[test]
+fn statement() {
+    check("(match x {}) - 1;");
+    check("match x {}.method();");
+    check("let _ = match x {} - 1;");
+}
+
+#[test]
+fn condition() {
+    check("if (S {}) == s {}");
+    check("if s == (S {}) {}");
+    check("while let Some(x) = (S {}).next() {}");
+    check("for x in (S {}) {}");
+}
@@ -0,0 +1,432 @@
+// Formats randomly generated functions and checks that the output parses back
+// to the same syntax tree, which catches any place where the printer drops
+// parentheses or braces that the syntax tree depends on. The parentheses from
+// the generated source are removed from the syntax tree before printing, so
+// the printer has to work out by itself where they are needed. The generator
+// is deterministic so that failures reproduce from the printed seed.
+
+use prettyplease::Options;
+use proc_macro2::{TokenStream, TokenTree};
+use std::mem;
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    token, AngleBracketedGenericArguments, Arm, BinOp, Block, Expr, ExprBlock, ExprClosure,
+    ExprMacro, File, Macro, Pat, ReturnType, Stmt,
+};
+
+const CASES: u64 = 2000;
+const MAX_DEPTH: usize = 4;
+
+#[test]
+fn generated_exprs() {
+    let mut skipped = 0;
+    for seed in 1..=CASES {
+        let source = Generator::new(seed).file();
+        let mut syntax_tree = match syn::parse_file(&source) {
+            Ok(syntax_tree) => syntax_tree,
+            // Many combinations of expressions are not valid syntax, such as
+            // `a < b < c` or a struct literal in the condition of an `if`.
+            // Nothing to check in that case.
+            Err(_) => {
+                skipped += 1;
+                continue;
+            }
+        };
+        normalize(&mut syntax_tree);
+        for max_width in [100, 40] {
+            let mut options = Options::default();
+            options.max_width = max_width;
+            let formatted = prettyplease::unparse_with(&syntax_tree, &options);
+            let mut reparsed = match syn::parse_file(&formatted) {
+                Ok(reparsed) => reparsed,
+                Err(error) => panic!(
+                    "seed {}, max_width {}: {}\n{}\n{}",
+                    seed, max_width, error, source, formatted,
+                ),
+            };
+            normalize(&mut reparsed);
+            assert!(
+                reparsed == syntax_tree,
+                "seed {}, max_width {}: output parses to a different syntax tree\n{}\n{}",
+                seed,
+                max_width,
+                source,
+                formatted,
+            );
+        }
+    }
+    assert!(
+        skipped < CASES * 3 / 4,
+        "too many invalid cases: {}",
+        skipped
+    );
+}
+
+// Remove parentheses from the syntax tree, along with the other differences
+// that the printer may introduce without changing the meaning: trailing commas,
+// braces around match arm and closure bodies, and the optional semicolons after
+// some statements.
+fn normalize(file: &mut File) {
+    struct Normalize;
+
+    impl VisitMut for Normalize {
+        fn visit_expr_mut(&mut self, expr: &mut Expr) {
+            while let Expr::Paren(paren) = expr {
+                *expr = (*paren.expr).clone();
+            }
+            match expr {
+                Expr::Array(expr) => {
+                    expr.elems.pop_punct();
+                }
+                Expr::Call(expr) => {
+                    expr.args.pop_punct();
+                }
+                Expr::MethodCall(expr) => {
+                    expr.args.pop_punct();
+                }
+                Expr::Struct(expr) if expr.rest.is_none() => {
+                    expr.fields.pop_punct();
+                }
+                Expr::Tuple(expr) if expr.elems.len() > 1 => {
+                    expr.elems.pop_punct();
+                }
+                _ => {}
+            }
+            visit_mut::visit_expr_mut(self, expr);
+        }
+
+        fn visit_pat_mut(&mut self, pat: &mut Pat) {
+            match pat {
+                Pat::Tuple(pat) if pat.elems.len() > 1 => {
+                    pat.elems.pop_punct();
+                }
+                Pat::TupleStruct(pat) => {
+                    pat.elems.pop_punct();
+                }
+                _ => {}
+            }
+            visit_mut::visit_pat_mut(self, pat);
+        }
+
+        fn visit_angle_bracketed_generic_arguments_mut(
+            &mut self,
+            arguments: &mut AngleBracketedGenericArguments,
+        ) {
+            arguments.args.pop_punct();
+            visit_mut::visit_angle_bracketed_generic_arguments_mut(self, arguments);
+        }
+
+        fn visit_arm_mut(&mut self, arm: &mut Arm) {
+            visit_mut::visit_arm_mut(self, arm);
+            unwrap_body(&mut arm.body);
+            if let Expr::Tuple(expr) = &*arm.body {
+                if expr.elems.is_empty() {
+                    *arm.body = Expr::Block(ExprBlock {
+                        attrs: Vec::new(),
+                        label: None,
+                        block: Block {
+                            brace_token: token::Brace::default(),
+                            stmts: Vec::new(),
+                        },
+                    });
+                }
+            }
+            arm.comma = None;
+        }
+
+        fn visit_expr_closure_mut(&mut self, closure: &mut ExprClosure) {
+            visit_mut::visit_expr_closure_mut(self, closure);
+            if let ReturnType::Default = closure.output {
+                unwrap_body(&mut closure.body);
+            }
+            closure.inputs.pop_punct();
+        }
+
+        fn visit_block_mut(&mut self, block: &mut Block) {
+            visit_mut::visit_block_mut(self, block);
+            for stmt in &mut block.stmts {
+                if let Stmt::Macro(mac) = stmt {
+                    *stmt = Stmt::Expr(
+                        Expr::Macro(ExprMacro {
+                            attrs: mem::take(&mut mac.attrs),
+                            mac: mac.mac.clone(),
+                        }),
+                        mac.semi_token,
+                    );
+                }
+                if let Stmt::Expr(expr, semi) = stmt {
+                    if is_unit(expr) {
+                        *semi = Some(token::Semi::default());
+                    } else if is_block_statement(expr) {
+                        *semi = None;
+                    }
+                }
+            }
+        }
+
+        fn visit_macro_mut(&mut self, mac: &mut Macro) {
+            let mut tokens: Vec<TokenTree> = mac.tokens.clone().into_iter().collect();
+            if let Some(TokenTree::Punct(punct)) = tokens.last() {
+                if punct.as_char() == ',' {
+                    tokens.pop();
+                }
+            }
+            mac.tokens = tokens.into_iter().collect::<TokenStream>();
+        }
+    }
+
+    fn unwrap_body(body: &mut Expr) {
+        while let Expr::Block(expr) = body {
+            if expr.label.is_some() || expr.block.stmts.len() != 1 {
+                return;
+            }
+            match &expr.block.stmts[0] {
+                Stmt::Expr(inner, _semi) => *body = inner.clone(),
+                _ => return,
+            }
+        }
+    }
+
+    // Expressions of type () that the printer ends with a semicolon at the
+    // end of a block.
+    fn is_unit(expr: &Expr) -> bool {
+        match expr {
+            Expr::Assign(_) | Expr::Break(_) | Expr::Continue(_) | Expr::Return(_) => true,
+            Expr::Binary(expr) => matches!(
+                expr.op,
+                BinOp::AddAssign(_)
+                    | BinOp::SubAssign(_)
+                    | BinOp::MulAssign(_)
+                    | BinOp::ShlAssign(_)
+                    | BinOp::BitOrAssign(_)
+            ),
+            _ => false,
+        }
+    }
+
+    // Statements of type () that the printer writes without a semicolon.
+    fn is_block_statement(expr: &Expr) -> bool {
+        match expr {
+            Expr::ForLoop(_) | Expr::While(_) => true,
+            Expr::If(expr) => match &expr.else_branch {
+                Some((_else_token, else_branch)) => is_block_statement(else_branch),
+                None => true,
+            },
+            _ => false,
+        }
+    }
+
+    Normalize.visit_file_mut(file);
+}
+
+struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    fn new(seed: u64) -> Self {
+        Generator {
+            state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1,
+        }
+    }
+
+    // Xorshift64*
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 32
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn choose<'a>(&mut self, options: &[&'a str]) -> &'a str {
+        options[self.below(options.len())]
+    }
+
+    fn file(&mut self) -> String {
+        let mut body = String::new();
+        for _ in 0..1 + self.below(3) {
+            body += &self.stmt(MAX_DEPTH);
+        }
+        let tail = self.expr(MAX_DEPTH);
+        format!("fn f() {{ {} {} }}", body, tail)
+    }
+
+    fn stmt(&mut self, depth: usize) -> String {
+        match self.below(4) {
+            0 => format!("let {} = {};", self.pat(), self.expr(depth)),
+            1 => format!("{} = {};", self.ident(), self.expr(depth)),
+            2 => format!("{};", self.blocklike(depth)),
+            _ => format!("{};", self.expr(depth)),
+        }
+    }
+
+    fn pat(&mut self) -> String {
+        match self.below(5) {
+            0 => "_".to_owned(),
+            1 => format!("({}, {})", self.ident(), self.ident()),
+            2 => format!("Some({})", self.ident()),
+            3 => format!("S {{ {}, .. }}", self.ident()),
+            _ => self.ident(),
+        }
+    }
+
+    fn ident(&mut self) -> String {
+        self.choose(&[
+            "a",
+            "b",
+            "x",
+            "value",
+            "long_variable_name_to_force_breaking",
+        ])
+        .to_owned()
+    }
+
+    fn ty(&mut self) -> String {
+        self.choose(&["u8", "i64", "usize", "f32", "&str", "Vec<u8>"])
+            .to_owned()
+    }
+
+    fn atom(&mut self) -> String {
+        match self.below(6) {
+            0 => self.choose(&["0", "1", "255u8", "1_000_000"]).to_owned(),
+            1 => self.choose(&["1.0", "2.5e3", "0.5f32"]).to_owned(),
+            2 => self.choose(&["\"\"", "\"string\"", "'c'"]).to_owned(),
+            3 => self.choose(&["true", "false", "()"]).to_owned(),
+            4 => self
+                .choose(&["std::f64::consts::PI", "Self::CONST"])
+                .to_owned(),
+            _ => self.ident(),
+        }
+    }
+
+    fn expr(&mut self, depth: usize) -> String {
+        if depth == 0 {
+            return self.atom();
+        }
+        let depth = depth - 1;
+        match self.below(22) {
+            0..=2 => {
+                let op = self.choose(&[
+                    "+", "-", "*", "/", "%", "&&", "||", "&", "|", "^", "<<", ">>", "==", "!=",
+                    "<", "<=", ">", ">=",
+                ]);
+                format!("{} {} {}", self.expr(depth), op, self.expr(depth))
+            }
+            3 => {
+                let op = self.choose(&["=", "+=", "-=", "*=", "<<=", "|="]);
+                format!("{} {} {}", self.expr(depth), op, self.expr(depth))
+            }
+            4 => {
+                let op = self.choose(&["-", "!", "*", "&", "&mut "]);
+                format!("{}{}", op, self.expr(depth))
+            }
+            5 => format!("{} as {}", self.expr(depth), self.ty()),
+            6 => {
+                let op = self.choose(&["..", "..="]);
+                match self.below(3) {
+                    0 => format!("{}{}", op, self.operand(depth)),
+                    1 if op == ".." => format!("{}..", self.operand(depth)),
+                    _ => format!("{}{}{}", self.operand(depth), op, self.operand(depth)),
+                }
+            }
+            7 => format!("({})", self.expr(depth)),
+            8 => format!("f({}, {})", self.expr(depth), self.expr(depth)),
+            9 => format!("{}.method({})", self.expr(depth), self.expr(depth)),
+            10 => format!("{}.field", self.expr(depth)),
+            11 => format!("{}[{}]", self.expr(depth), self.expr(depth)),
+            12 => format!("{}?", self.expr(depth)),
+            // A closure body extends as far to the right as it can, so keep
+            // closures inside delimiters rather than as operands.
+            13 => match self.below(3) {
+                0 => format!("f(|{}| {})", self.ident(), self.expr(depth)),
+                1 => format!("f(move || {})", self.expr(depth)),
+                _ => format!(
+                    "f(|{}: {}| -> {} {{ {} }})",
+                    self.ident(),
+                    self.ty(),
+                    self.ty(),
+                    self.expr(depth),
+                ),
+            },
+            14 => format!(
+                "S {{ field: {}, other: {} }}",
+                self.expr(depth),
+                self.expr(depth),
+            ),
+            15 => format!(
+                "S {{ field: {}, ..{} }}",
+                self.expr(depth),
+                self.expr(depth)
+            ),
+            16 => match self.below(4) {
+                0 => "return".to_owned(),
+                1 => format!("return {}", self.expr(depth)),
+                2 => "break".to_owned(),
+                _ => format!("break 'label {}", self.expr(depth)),
+            },
+            17 => format!("({}, {})", self.expr(depth), self.expr(depth)),
+            18 => match self.below(2) {
+                0 => format!("[{}, {}]", self.expr(depth), self.expr(depth)),
+                _ => format!("[{}; {}]", self.expr(depth), self.expr(depth)),
+            },
+            19 => format!("vec![{}, {}]", self.expr(depth), self.expr(depth)),
+            _ => self.blocklike(depth),
+        }
+    }
+
+    // Range operators do not associate, and syn versions disagree on how a
+    // chain of them such as `..a..=b` parses, so the endpoints of a range are
+    // limited to postfix expressions.
+    fn operand(&mut self, depth: usize) -> String {
+        match self.below(5) {
+            0 => format!("f({})", self.expr(depth)),
+            1 => format!("{}.method()", self.operand(depth)),
+            2 => format!("({})", self.expr(depth)),
+            _ => self.atom(),
+        }
+    }
+
+    fn blocklike(&mut self, depth: usize) -> String {
+        match self.below(7) {
+            0 => format!(
+                "if {} {{ {} }} else {{ {} }}",
+                self.expr(depth),
+                self.expr(depth),
+                self.expr(depth),
+            ),
+            1 => format!(
+                "if let {} = {} {{ {} }}",
+                self.pat(),
+                self.expr(depth),
+                self.stmt(depth),
+            ),
+            2 => format!(
+                "match {} {{ {} => {}, {} if {} => {}, _ => {{}} }}",
+                self.expr(depth),
+                self.pat(),
+                self.expr(depth),
+                self.pat(),
+                self.expr(depth),
+                self.expr(depth),
+            ),
+            3 => format!("while {} {{ {} }}", self.expr(depth), self.stmt(depth)),
+            4 => format!(
+                "for {} in {} {{ {} }}",
+                self.pat(),
+                self.expr(depth),
+                self.stmt(depth),
+            ),
+            5 => format!("'label: loop {{ {} }}", self.stmt(depth)),
+            _ => format!(
+                "{}{{ {} {} }}",
+                self.choose(&["", "unsafe "]),
+                self.stmt(depth),
+                self.expr(depth),
+            ),
+        }
+    }
+}
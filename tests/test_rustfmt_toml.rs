@@ -0,0 +1,190 @@
+use prettyplease::{
+    BraceStyle, ImportsGranularity, MatchArmLeadingPipes, NewlineStyle, RustfmtConfig,
+    SmallHeuristics,
+};
+use std::fs;
+
+#[test]
+fn test_parse() {
+    let contents = r#"
+        # Shared with rustfmt.
+        max_width = 100
+        tab_spaces = 2 # comment
+        hard_tabs = true
+        newline_style = "Windows"
+        use_small_heuristics = "Max"
+        fn_call_width = 1_000
+        imports_granularity = 'Crate'
+        reorder_imports = true
+        brace_style = "AlwaysNextLine"
+        where_single_line = true
+        match_arm_leading_pipes = "Preserve"
+        normalize_doc_attributes = true
+        edition = "2021"
+        ignore = [
+            "src/generated.rs", # generated
+            "benches",
+        ]
+        "brace_style" = "PreferSameLine"
+    "#;
+    let error = RustfmtConfig::parse(contents).unwrap_err();
+    assert_eq!(error.to_string(), "line 20: duplicate key `brace_style`");
+
+    let contents = contents.replace("\"brace_style\" = \"PreferSameLine\"", "");
+    let config = RustfmtConfig::parse(&contents).unwrap();
+    let options = &config.options;
+    assert_eq!(options.max_width, 100);
+    assert_eq!(options.tab_spaces, 2);
+    assert!(options.hard_tabs);
+    assert_eq!(options.newline_style, NewlineStyle::Windows);
+    assert_eq!(options.use_small_heuristics, SmallHeuristics::Max);
+    assert_eq!(options.fn_call_width, 1000);
+    assert_eq!(options.imports_granularity, ImportsGranularity::Crate);
+    assert!(options.reorder_imports);
+    assert_eq!(options.brace_style, BraceStyle::AlwaysNextLine);
+    assert!(options.where_single_line);
+    assert_eq!(
+        options.match_arm_leading_pipes,
+        MatchArmLeadingPipes::Preserve
+    );
+    assert!(options.normalize_doc_attributes);
+    assert_eq!(config.path, None);
+    assert_eq!(config.unsupported, ["edition", "ignore"]);
+}
+
+#[test]
+fn test_defaults() {
+    let config = RustfmtConfig::parse("# empty\n").unwrap();
+    assert_eq!(config.options.max_width, 100);
+    assert_eq!(config.options.tab_spaces, 4);
+    #[cfg(feature = "span-locations")]
+    assert_eq!(config.options.blank_lines, 1);
+    assert!(config.unsupported.is_empty());
+}
+
+#[test]
+fn test_unsupported_value() {
+    let config = RustfmtConfig::parse("brace_style = \"PreferSameLine\"\n").unwrap();
+    assert_eq!(config.options.brace_style, BraceStyle::SameLineWhere);
+    assert_eq!(config.unsupported, ["brace_style"]);
+}
+
+#[test]
+fn test_errors() {
+    for (contents, expected) in [
+        (
+            "max_width = \"100\"",
+            "line 1: invalid value for `max_width`: expected an integer, found a string",
+        ),
+        (
+            "\nmax_width = -1",
+            "line 2: invalid value for `max_width`: expected a non-negative integer, found -1",
+        ),
+        ("hard_tabs = yes", "line 1: unsupported value `yes`"),
+        (
+            "use_small_heuristics = \"Min\"",
+            "line 1: invalid value for `use_small_heuristics`: unknown variant `Min`",
+        ),
+        ("max_width 100", "line 1: expected `=` after key"),
+        (
+            "max_width = 100 100",
+            "line 1: expected newline after value",
+        ),
+        (
+            "[options]\nmax_width = 100",
+            "line 1: tables are not supported",
+        ),
+        ("edition = \"2021", "line 1: unterminated string"),
+    ] {
+        let error = RustfmtConfig::parse(contents).unwrap_err();
+        assert_eq!(error.to_string(), expected);
+    }
+}
+
+#[test]
+fn test_find() {
+    let root = std::env::temp_dir().join(format!("prettyplease-rustfmt-{}", std::process::id()));
+    let nested = root.join("crate").join("src");
+    fs::create_dir_all(&nested).unwrap();
+    let file = nested.join("lib.rs");
+    fs::write(&file, "").unwrap();
+
+    fs::write(root.join("rustfmt.toml"), "max_width = 80\n").unwrap();
+    let config = RustfmtConfig::find(&file).unwrap();
+    assert_eq!(config.options.max_width, 80);
+    assert_eq!(
+        config.path,
+        Some(root.join("rustfmt.toml").canonicalize().unwrap())
+    );
+
+    fs::write(
+        root.join("crate").join(".rustfmt.toml"),
+        "max_width = 120\n",
+    )
+    .unwrap();
+    fs::write(root.join("crate").join("rustfmt.toml"), "max_width = 1\n").unwrap();
+    assert_eq!(RustfmtConfig::find(&nested).unwrap().options.max_width, 120);
+
+    let missing = nested.join("generated").join("mod.rs");
+    let config = RustfmtConfig::find(&missing).unwrap();
+    assert_eq!(config.options.max_width, 120);
+    assert_eq!(
+        config.path,
+        Some(
+            root.join("crate")
+                .join(".rustfmt.toml")
+                .canonicalize()
+                .unwrap()
+        )
+    );
+
+    fs::write(
+        root.join("crate").join(".rustfmt.toml"),
+        "max_width = true\n",
+    )
+    .unwrap();
+    let error = RustfmtConfig::find(&file).unwrap_err();
+    assert_eq!(
+        error.path(),
+        Some(
+            &*root
+                .join("crate")
+                .join(".rustfmt.toml")
+                .canonicalize()
+                .unwrap()
+        )
+    );
+    assert_eq!(error.line(), Some(1));
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_derived_widths() {
+    let options = RustfmtConfig::parse("").unwrap().options;
+    assert_eq!(options.struct_lit_width, 18);
+    assert_eq!(options.fn_call_width, 60);
+    assert_eq!(options.chain_width, 60);
+    assert_eq!(options.array_width, 60);
+
+    let options = RustfmtConfig::parse("max_width = 120\nchain_width = 100\n")
+        .unwrap()
+        .options;
+    assert_eq!(options.struct_lit_width, 21);
+    assert_eq!(options.fn_call_width, 72);
+    assert_eq!(options.chain_width, 100);
+    assert_eq!(options.array_width, 72);
+
+    // The arguments are 78 columns, over 60% of 120 although the line fits.
+    let input = "fn f() {\n    g(first_argument, second_argument, third_argument, fourth_argument, fifth);\n}\n";
+    let syntax_tree = syn::parse_file(input).unwrap();
+    let options = RustfmtConfig::parse("max_width = 120\n").unwrap().options;
+    assert_eq!(
+        prettyplease::unparse_with(&syntax_tree, &options),
+        "fn f() {\n    g(\n        first_argument,\n        second_argument,\n        third_argument,\n        fourth_argument,\n        fifth,\n    );\n}\n",
+    );
+    let options = RustfmtConfig::parse("max_width = 120\nfn_call_width = 80\n")
+        .unwrap()
+        .options;
+    assert_eq!(prettyplease::unparse_with(&syntax_tree, &options), input);
+}
@@ -0,0 +1,81 @@
+use prettyplease::{NewlineStyle, Options, SourceMap};
+
+// The text of the token mapped at a line and column of formatted output.
+#[track_caller]
+fn token_at<'a>(formatted: &'a str, source_map: &SourceMap, line: usize, column: usize) -> &'a str {
+    let mapping = source_map.lookup(line, column).unwrap();
+    &formatted[mapping.offset..][..mapping.len]
+}
+
+#[test]
+fn test_lines() {
+    let syntax_tree = syn::parse_file("fn f(a: u8) -> u8 { let b = a.0; b + 'x' as u8 }").unwrap();
+    let (formatted, source_map) =
+        prettyplease::unparse_with_source_map(&syntax_tree, &Options::default());
+    assert_eq!(
+        formatted,
+        "fn f(a: u8) -> u8 {\n    let b = a.0;\n    b + 'x' as u8\n}\n",
+    );
+    assert_eq!(token_at(&formatted, &source_map, 1, 3), "f");
+    assert_eq!(token_at(&formatted, &source_map, 1, 8), "u8");
+    assert_eq!(token_at(&formatted, &source_map, 2, 8), "b");
+    assert_eq!(token_at(&formatted, &source_map, 2, 14), "0");
+    assert_eq!(token_at(&formatted, &source_map, 3, 8), "'x'");
+    // Between tokens, and on keywords and punctuation, the closest token
+    // before the position is found.
+    assert_eq!(token_at(&formatted, &source_map, 3, 11), "'x'");
+    assert_eq!(token_at(&formatted, &source_map, 4, 0), "u8");
+    assert!(source_map.lookup(1, 0).is_none());
+
+    let mapping = source_map.lookup(3, 4).unwrap();
+    assert_eq!((mapping.line, mapping.column), (3, 4));
+    assert_eq!(mapping.offset, formatted.find("b + ").unwrap());
+}
+
+#[test]
+fn test_hard_tabs() {
+    let syntax_tree = syn::parse_file("fn f() { if a { b(\"é\", c); } }").unwrap();
+    let mut options = Options::default();
+    options.hard_tabs = true;
+    let (formatted, source_map) = prettyplease::unparse_with_source_map(&syntax_tree, &options);
+    assert_eq!(formatted, "fn f() {\n\tif a {\n\t\tb(\"é\", c);\n\t}\n}\n");
+    // Columns count one per tab and per char, while offsets are in bytes.
+    let mapping = source_map.lookup(3, 2).unwrap();
+    assert_eq!(mapping.column, 2);
+    assert_eq!(&formatted[mapping.offset..][..mapping.len], "b");
+    let mapping = source_map.lookup(3, 9).unwrap();
+    assert_eq!(mapping.column, 9);
+    assert_eq!(mapping.offset, formatted.find("c)").unwrap());
+    assert_eq!(token_at(&formatted, &source_map, 3, 4), "\"é\"");
+}
+
+#[test]
+fn test_multi_line_string() {
+    let syntax_tree = syn::parse_file("fn f() { g(\"one\ntwo\"); h(x); }").unwrap();
+    let (formatted, source_map) =
+        prettyplease::unparse_with_source_map(&syntax_tree, &Options::default());
+    assert_eq!(formatted, "fn f() {\n    g(\"one\ntwo\");\n    h(x);\n}\n");
+    assert_eq!(token_at(&formatted, &source_map, 2, 6), "\"one\ntwo\"");
+    // Lines after the literal's own line break are counted.
+    assert_eq!(token_at(&formatted, &source_map, 3, 2), "\"one\ntwo\"");
+    assert_eq!(token_at(&formatted, &source_map, 4, 4), "h");
+    assert_eq!(token_at(&formatted, &source_map, 4, 6), "x");
+}
+
+#[test]
+fn test_windows_newlines() {
+    let syntax_tree = syn::parse_file("fn f() { g(\"one\ntwo\"); h(x); }").unwrap();
+    let mut options = Options::default();
+    options.newline_style = NewlineStyle::Windows;
+    let (formatted, source_map) = prettyplease::unparse_with_source_map(&syntax_tree, &options);
+    assert_eq!(
+        formatted,
+        "fn f() {\r\n    g(\"one\r\ntwo\");\r\n    h(x);\r\n}\r\n",
+    );
+    assert_eq!(token_at(&formatted, &source_map, 1, 3), "f");
+    assert_eq!(token_at(&formatted, &source_map, 2, 6), "\"one\r\ntwo\"");
+    assert_eq!(token_at(&formatted, &source_map, 4, 4), "h");
+    let mapping = source_map.lookup(4, 6).unwrap();
+    assert_eq!(mapping.column, 6);
+    assert_eq!(mapping.offset, formatted.find("x)").unwrap());
+}
@@ -0,0 +1,58 @@
+mod common;
+
+#[track_caller]
+fn check(input: &str, expected: &str) {
+    let mut options = prettyplease::Options::default();
+    options.remove_redundant_parens = true;
+    let input = format!("fn f() {{\n    {}\n}}\n", input);
+    let expected = format!("fn f() {{\n    {}\n}}\n", expected);
+    common::check(&options, &input, &expected);
+}
+
+#[test]
+fn expr() {
+    check("let _ = (a) + ((b));", "let _ = a + b;");
+    check("let _ = ((a + b)) * c;", "let _ = (a + b) * c;");
+    check("let _ = a + (b * c);", "let _ = a + b * c;");
+    check("let _ = (f)((x), (y));", "let _ = f(x, y);");
+    check("let _ = (a.f)();", "let _ = (a.f)();");
+    check("let _ = (x as u8) < y;", "let _ = (x as u8) < y;");
+    check("let _ = &(*x);", "let _ = &*x;");
+    check("let _ = #[attr] (a + b);", "let _ = #[attr] (a + b);");
+    check("(match x {}).f();", "match x {}.f();");
+    check("(match x {}) - 1;", "(match x {}) - 1;");
+    check("if (S {}) == s {}", "if (S {}) == s {}");
+    check(
+        "let _ = (a || b) else { return };",
+        "let _ = (a || b) else { return };",
+    );
+}
+
+#[test]
+fn literal() {
+    check("let _ = (1).0;", "let _ = (1).0;");
+    check("let _ = (1).x;", "let _ = 1.x;");
+    check("let _ = (1.).f();", "let _ = (1.).f();");
+    check("let _ = (1.0).f();", "let _ = 1.0.f();");
+}
+
+#[test]
+fn ty() {
+    check("let _: (u8) = 0;", "let _: u8 = 0;");
+    check("let _: &(dyn A) = x;", "let _: &dyn A = x;");
+    check("let _: &(dyn A + Send) = x;", "let _: &(dyn A + Send) = x;");
+    check("let _: Vec<(T)> = v;", "let _: Vec<T> = v;");
+}
+
+#[test]
+fn pat() {
+    check("let (x) = 0;", "let x = 0;");
+    check(
+        "let Some((x)) = y else { return };",
+        "let Some(x) = y else { return };",
+    );
+    check("let &(mut x) = y;", "let &(mut x) = y;");
+    check("let &(ref x) = y;", "let &ref x = y;");
+    check("let [(1..), x] = y;", "let [(1..), x] = y;");
+    check("let f = |(A | B)| 0;", "let f = |(A | B)| 0;");
+}
@@ -0,0 +1,78 @@
+use prettyplease::{Options, WriteError};
+use syn::{Expr, File, Stmt};
+
+// A file whose main function returns an expression made of tokens that are
+// not any syntax prettyplease knows how to print.
+fn unknown_expr() -> File {
+    let mut syntax_tree: File = syn::parse_str("fn main() { let x = 0; }").unwrap();
+    let syn::Item::Fn(main) = &mut syntax_tree.items[0] else {
+        unreachable!()
+    };
+    let Stmt::Local(local) = &mut main.block.stmts[0] else {
+        unreachable!()
+    };
+    let tokens = "$ unknown syntax".parse().unwrap();
+    *local.init.as_mut().unwrap().expr = Expr::Verbatim(tokens);
+    syntax_tree
+}
+
+#[test]
+fn test_try_unparse() {
+    let syntax_tree = unknown_expr();
+    let error = prettyplease::try_unparse(&syntax_tree).unwrap_err();
+    assert_eq!(error.kind(), "Expr::Verbatim");
+    assert_eq!(error.tokens(), Some("$ unknown syntax"));
+    assert_eq!(error.to_string(), "Expr::Verbatim `$ unknown syntax`");
+}
+
+#[test]
+#[should_panic = "Expr::Verbatim `$ unknown syntax`"]
+fn test_unparse() {
+    let syntax_tree = unknown_expr();
+    prettyplease::unparse(&syntax_tree);
+}
+
+#[test]
+fn test_unparse_to_writer() {
+    let syntax_tree = unknown_expr();
+
+    let mut bytes = Vec::new();
+    let error = prettyplease::unparse_to_writer(&syntax_tree, &mut bytes).unwrap_err();
+    let WriteError::Unsupported(error) = error else {
+        panic!("{:?}", error);
+    };
+    assert_eq!(error.kind(), "Expr::Verbatim");
+
+    let mut string = String::new();
+    let error = prettyplease::unparse_to_fmt_writer(&syntax_tree, &mut string).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "unable to format Expr::Verbatim `$ unknown syntax`",
+    );
+}
+
+#[test]
+fn test_fallback() {
+    let syntax_tree = unknown_expr();
+    let mut options = Options::default();
+    options.verbatim_fallback = true;
+
+    let formatted = prettyplease::try_unparse_with(&syntax_tree, &options).unwrap();
+    assert_eq!(formatted, "fn main() {\n    let x = $unknown syntax;\n}\n");
+
+    let (formatted, fallbacks) = prettyplease::unparse_with_report(&syntax_tree, &options);
+    assert_eq!(formatted, "fn main() {\n    let x = $unknown syntax;\n}\n");
+    assert_eq!(fallbacks.len(), 1);
+    assert_eq!(fallbacks[0].kind(), "Expr::Verbatim");
+    assert_eq!(fallbacks[0].tokens(), Some("$ unknown syntax"));
+}
+
+#[test]
+fn test_no_fallback() {
+    let syntax_tree: File = syn::parse_str("fn main() { let x = 0; }").unwrap();
+    let mut options = Options::default();
+    options.verbatim_fallback = true;
+    let (formatted, fallbacks) = prettyplease::unparse_with_report(&syntax_tree, &options);
+    assert_eq!(formatted, "fn main() {\n    let x = 0;\n}\n");
+    assert!(fallbacks.is_empty());
+}
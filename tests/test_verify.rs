@@ -0,0 +1,142 @@
+use prettyplease::VerifyError;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn cargo_expand_corpus() {
+    let corpus_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("cargo-expand");
+    let options = prettyplease::Options::default();
+
+    let mut failures = Vec::new();
+    for entry in fs::read_dir(corpus_dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension() != Some(OsStr::new("rs")) {
+            continue;
+        }
+
+        let input_contents = fs::read_to_string(&path).unwrap();
+        let syntax_tree = syn::parse_file(&input_contents).unwrap();
+        match prettyplease::unparse_verified(&syntax_tree, &options) {
+            Ok(_) => {}
+            // Newer versions of syn can parse syntax that prettyplease does
+            // not know how to print yet. That is not what this test is about.
+            Err(VerifyError::Unsupported(error)) => {
+                eprintln!("skipping {}: {}", path.display(), error);
+            }
+            Err(error) => failures.push(format!("{}: {}", path.display(), error)),
+        }
+    }
+
+    if !failures.is_empty() {
+        panic!("\n{}", failures.join("\n"));
+    }
+}
+
+#[test]
+fn windows_newlines() {
+    let input = "/// First line.\n/// Second line.\n/**\n * Block.\n */\npub fn f() {\n    let s = \"a\nb\";\n}\n";
+    let syntax_tree = syn::parse_file(input).unwrap();
+    let mut options = prettyplease::Options::default();
+    options.newline_style = prettyplease::NewlineStyle::Windows;
+    let formatted = prettyplease::unparse_verified(&syntax_tree, &options).unwrap();
+    assert_eq!(formatted, input.replace('\n', "\r\n"));
+}
+
+#[test]
+fn string_values() {
+    let input = r#"
+        #[doc = "p
+q"]
+        #[doc = "tab\there"]
+        fn f() {
+            let s = "a\nb";
+            let r = r"c\d";
+            let escaped = "\u{1F600}\x41\r\n";
+        }
+    "#;
+    let syntax_tree = syn::parse_file(input).unwrap();
+    for newline_style in [
+        prettyplease::NewlineStyle::Unix,
+        prettyplease::NewlineStyle::Windows,
+    ] {
+        let mut options = prettyplease::Options::default();
+        options.newline_style = newline_style;
+        prettyplease::unparse_verified(&syntax_tree, &options).unwrap();
+    }
+}
+
+#[test]
+fn trailing_commas() {
+    let input = r#"
+        #[derive(Clone, Debug,)]
+        #[cfg_attr(test, derive(PartialEq,))]
+        enum E<T: Copy,> where T: Default, { A(T,), B { t: T, }, }
+        use std::{fmt, io,};
+        fn f(x: (u8,), y: (u8, u16,), z: fn(u8,) -> u8,) -> (u8,) {
+            let (a,) = x;
+            let [b, c,] = [y.0, z(a,),];
+            let g = E::B { t: c, };
+            let E::B { t, } = g else { return (b,); };
+            match (t,) {
+                (0,) => {},
+                _ => {}
+            }
+            let d = a & &b;
+            let e = a && b;
+            (a,)
+        }
+    "#;
+    let syntax_tree = syn::parse_file(input).unwrap();
+    let options = prettyplease::Options::default();
+    prettyplease::unparse_verified(&syntax_tree, &options).unwrap();
+}
+
+#[test]
+fn normalized_docs() {
+    let input = r#"
+        #[doc = "/ slash"]
+        #[doc = "x\r\ny"]
+        #[doc = "\n"]
+        #[doc = "a\n  b"]
+        struct S;
+    "#;
+    let syntax_tree = syn::parse_file(input).unwrap();
+    let mut options = prettyplease::Options::default();
+    options.normalize_doc_attributes = true;
+    prettyplease::unparse_verified(&syntax_tree, &options).unwrap();
+}
+
+#[cfg(feature = "unparen")]
+#[test]
+fn redundant_parens() {
+    let input = "fn f() { let x = ((a + b)) * c; let y = a + (b * c); let z = (-(x)).f(); }";
+    let mut syntax_tree = syn::parse_file(input).unwrap();
+    let mut options = prettyplease::Options::default();
+    options.remove_redundant_parens = true;
+    let formatted = prettyplease::unparse_verified(&syntax_tree, &options).unwrap();
+    let expected = "fn f() {\n    let x = (a + b) * c;\n    let y = a + b * c;\n    let z = (-x).f();\n}\n";
+    assert_eq!(formatted, expected);
+
+    // A syntax tree without the parentheses that precedence requires, as code
+    // generators build it.
+    let syn::Item::Fn(f) = &mut syntax_tree.items[0] else {
+        unreachable!()
+    };
+    let syn::Stmt::Local(local) = &mut f.block.stmts[0] else {
+        unreachable!()
+    };
+    let init = local.init.as_mut().unwrap();
+    let syn::Expr::Binary(binary) = &mut *init.expr else {
+        unreachable!()
+    };
+    let syn::Expr::Paren(paren) = &mut *binary.left else {
+        unreachable!()
+    };
+    let syn::Expr::Paren(inner) = &*paren.expr else {
+        unreachable!()
+    };
+    binary.left = inner.expr.clone();
+    let formatted = prettyplease::unparse_verified(&syntax_tree, &options).unwrap();
+    assert_eq!(formatted, expected);
+}
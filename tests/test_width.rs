@@ -0,0 +1,52 @@
+mod common;
+
+use crate::common::check;
+use prettyplease::{Options, StrWidth};
+
+const INPUT: &str = "
+    fn f() {
+        g(\"日本語のテキスト\", \"絵文字 🦀🦀🦀\", \"e\u{301}\u{301}\u{301}\u{301}\u{301}\u{301}\");
+    }
+";
+
+#[test]
+fn test_display_width() {
+    let mut options = Options::default();
+    options.max_width = 60;
+    options.min_space = 0;
+    let expected = "\
+        fn f() {\n    \
+            g(\"日本語のテキスト\", \"絵文字 🦀🦀🦀\", \"e\u{301}\u{301}\u{301}\u{301}\u{301}\u{301}\");\n\
+        }\n\
+    ";
+    check(&options, INPUT, expected);
+}
+
+#[test]
+fn test_byte_width() {
+    let mut options = Options::default();
+    options.max_width = 60;
+    options.min_space = 0;
+    options.str_width = StrWidth(str::len);
+    let expected = "\
+        fn f() {\n    \
+            g(\n        \
+                \"日本語のテキスト\",\n        \
+                \"絵文字 🦀🦀🦀\",\n        \
+                \"e\u{301}\u{301}\u{301}\u{301}\u{301}\u{301}\",\n    \
+            );\n\
+        }\n\
+    ";
+    check(&options, INPUT, expected);
+}
+
+#[test]
+fn test_unassigned_and_tags() {
+    let StrWidth(width) = Options::default().str_width;
+    assert_eq!(width("\u{378}"), 1);
+    assert_eq!(width("\u{1FBFA}"), 1);
+    assert_eq!(width("\u{10FFFF}"), 1);
+    assert_eq!(width("\u{E0061}"), 0);
+    assert_eq!(width("\u{3FFFD}"), 2);
+    assert_eq!(width("日本"), 4);
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use crate::common::check;
+use prettyplease::Options;
+
+fn options() -> Options {
+    let mut options = Options::default();
+    options.max_width = 40;
+    options.min_space = 0;
+    options.wrap_doc_comments = true;
+    options
+}
+
+#[test]
+fn test_paragraph() {
+    let input = "
+        //! The quick brown fox jumps over the lazy dog.
+        /// The quick brown fox jumps over the lazy dog.
+        mod m {
+            /// The quick brown fox jumps over the lazy dog.
+            fn f() {}
+        }
+    ";
+    let expected = "\
+        //! The quick brown fox jumps over the\n\
+        //! lazy dog.\n\
+        /// The quick brown fox jumps over the\n\
+        /// lazy dog.\n\
+        mod m {\n    \
+            /// The quick brown fox jumps over\n    \
+            /// the lazy dog.\n    \
+            fn f() {}\n\
+        }\n\
+    ";
+    check(&options(), input, expected);
+}
+
+#[test]
+fn test_markdown() {
+    let input = r#"
+        /// - The quick brown fox jumps over the lazy dog.
+        /// 10. The quick brown fox jumps over the lazy dog.
+        ///
+        /// # The quick brown fox jumps over the lazy dog
+        ///
+        /// ```
+        /// let quick_brown_fox = jumps_over(lazy, dog);
+        /// ```
+        ///
+        /// | The quick brown fox | jumps over the lazy dog |
+        ///
+        /// The quick brown fox jumps `over the lazy` dog.
+        ///
+        /// The quick brown fox [jumps over the](lazy) dog.
+        ///
+        /// The quick brown foxes jump over 2 - 3 lazy dogs.
+        struct S;
+    "#;
+    let expected = "\
+        /// - The quick brown fox jumps over the\n\
+        ///   lazy dog.\n\
+        /// 10. The quick brown fox jumps over\n\
+        ///     the lazy dog.\n\
+        ///\n\
+        /// # The quick brown fox jumps over the lazy dog\n\
+        ///\n\
+        /// ```\n\
+        /// let quick_brown_fox = jumps_over(lazy, dog);\n\
+        /// ```\n\
+        ///\n\
+        /// | The quick brown fox | jumps over the lazy dog |\n\
+        ///\n\
+        /// The quick brown fox jumps\n\
+        /// `over the lazy` dog.\n\
+        ///\n\
+        /// The quick brown fox\n\
+        /// [jumps over the](lazy) dog.\n\
+        ///\n\
+        /// The quick brown foxes jump over 2 -\n\
+        /// 3 lazy dogs.\n\
+        struct S;\n\
+    ";
+    check(&options(), input, expected);
+}
+
+#[test]
+fn test_join() {
+    let input = r#"
+        /// The quick brown fox
+        /// jumps over the lazy dog.  The quick brown fox
+        /// jumps over the lazy dog.
+        ///
+        /// - The quick brown fox
+        ///   jumps over the lazy dog.
+        /// - The quick brown fox
+        /// # The quick brown fox
+        /// The quick brown fox\
+        /// jumps over the lazy dog.
+        /// ```
+        /// let quick_brown_fox =
+        ///     jumps_over(lazy, dog);
+        /// ```
+        #[doc = " The quick brown fox"]
+        #[inline]
+        /// jumps over the lazy dog.
+        fn f() {}
+    "#;
+    let expected = "\
+        /// The quick brown fox jumps over the\n\
+        /// lazy dog. The quick brown fox jumps\n\
+        /// over the lazy dog.\n\
+        ///\n\
+        /// - The quick brown fox jumps over the\n\
+        ///   lazy dog.\n\
+        /// - The quick brown fox\n\
+        /// # The quick brown fox\n\
+        /// The quick brown fox\\\n\
+        /// jumps over the lazy dog.\n\
+        /// ```\n\
+        /// let quick_brown_fox =\n\
+        ///     jumps_over(lazy, dog);\n\
+        /// ```\n\
+        /// The quick brown fox\n\
+        #[inline]\n\
+        /// jumps over the lazy dog.\n\
+        fn f() {}\n\
+    ";
+    check(&options(), input, expected);
+}
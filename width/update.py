@@ -0,0 +1,111 @@
+#!/usr/bin/env python3
+
+# Regenerates the ZERO_WIDTH and WIDE tables at the bottom of src/width.rs
+# from the Unicode Character Database bundled with Python's unicodedata
+# module. Run from the root of the repository:
+#
+#     python3 width/update.py
+
+import os
+import sys
+import unicodedata
+
+WIDTH_RS = os.path.join(os.path.dirname(__file__), "..", "src", "width.rs")
+MARKER = "// Generated by width/update.py"
+
+# Prepended_Concatenation_Mark from PropList.txt. These have general category
+# Cf but are printed as a visible sign spanning the digits that follow them.
+PREPENDED_CONCATENATION_MARK = [
+    (0x0600, 0x0605),
+    (0x06DD, 0x06DD),
+    (0x070F, 0x070F),
+    (0x0890, 0x0891),
+    (0x08E2, 0x08E2),
+    (0x110BD, 0x110BD),
+    (0x110CD, 0x110CD),
+]
+
+# Hangul Jamo medial vowels and final consonants, which combine with a
+# preceding initial consonant into one syllable block.
+HANGUL_JUNGSEONG_JONGSEONG = [
+    (0x1160, 0x11FF),
+    (0xD7B0, 0xD7FF),
+]
+
+
+def in_ranges(cp, ranges):
+    return any(lo <= cp <= hi for lo, hi in ranges)
+
+
+def is_zero_width(cp):
+    if in_ranges(cp, HANGUL_JUNGSEONG_JONGSEONG):
+        return True
+    category = unicodedata.category(chr(cp))
+    if category in ("Mn", "Me"):
+        return True
+    # U+00AD SOFT HYPHEN is shown as a hyphen where a line is broken at it.
+    return category == "Cf" and cp != 0x00AD and not in_ranges(cp, PREPENDED_CONCATENATION_MARK)
+
+
+# Unassigned code points that EastAsianWidth.txt defaults to W rather than N.
+RESERVED_WIDE = [
+    (0x3400, 0x4DBF),
+    (0x4E00, 0x9FFF),
+    (0xF900, 0xFAFF),
+    (0x20000, 0x2FFFD),
+    (0x30000, 0x3FFFD),
+]
+
+
+def is_wide(cp):
+    # Python's east_asian_width says F for some unassigned code points, which
+    # EastAsianWidth.txt leaves at the default of N.
+    if unicodedata.category(chr(cp)) == "Cn":
+        return in_ranges(cp, RESERVED_WIDE)
+    return unicodedata.east_asian_width(chr(cp)) in ("W", "F")
+
+
+def ranges(predicate):
+    table = []
+    for cp in range(0x80, sys.maxunicode + 1):
+        if 0xD800 <= cp <= 0xDFFF or not predicate(cp):
+            continue
+        if table and table[-1][1] == cp - 1:
+            table[-1][1] = cp
+        else:
+            table.append([cp, cp])
+    return table
+
+
+def table(name, entries):
+    lines = ["const %s: &[(u32, u32)] = &[" % name]
+    for lo, hi in entries:
+        lines.append("    (0x%04X, 0x%04X)," % (lo, hi))
+    lines.append("];")
+    return "\n".join(lines) + "\n"
+
+
+def main():
+    with open(WIDTH_RS) as f:
+        source = f.read()
+    prefix = source[: source.index(MARKER)]
+    generated = "\n".join(
+        [
+            MARKER + " from version %s of the Unicode Character"
+            % unicodedata.unidata_version,
+            "// Database: general categories Mn, Me and Cf from UnicodeData.txt, the",
+            "// Prepended_Concatenation_Mark property from PropList.txt, and",
+            "// East_Asian_Width W and F from EastAsianWidth.txt, where unassigned code",
+            "// points are W only in the reserved CJK ideograph ranges. Do not edit by",
+            "// hand.",
+            "",
+            table("ZERO_WIDTH", ranges(is_zero_width)),
+            table("WIDE", ranges(is_wide)),
+        ]
+    )
+    with open(WIDTH_RS, "w") as f:
+        f.write(prefix + generated.rstrip("\n") + "\n")
+
+
+if __name__ == "__main__":
+    main()